@@ -0,0 +1,16 @@
+fn main() {
+    // `FirebaseConfig::from_env` reads these with `option_env!`, so the
+    // crate must be rebuilt whenever they change
+    for var in [
+        "FIREBASE_API_KEY",
+        "FIREBASE_AUTH_DOMAIN",
+        "FIREBASE_DATABASE_URL",
+        "FIREBASE_PROJECT_ID",
+        "FIREBASE_STORAGE_BUCKET",
+        "FIREBASE_MESSAGING_SENDER_ID",
+        "FIREBASE_APP_ID",
+        "FIREBASE_MEASUREMENT_ID",
+    ] {
+        println!("cargo:rerun-if-env-changed={var}");
+    }
+}
@@ -0,0 +1,130 @@
+use crate::FirebaseError;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+#[serde_with::skip_serializing_none]
+#[derive(Clone, Debug, PartialEq, Eq, TypedBuilder, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FirebaseConfig {
+    #[builder(setter(into))]
+    pub api_key: String,
+    #[builder(default, setter(into, strip_option))]
+    pub auth_domain: Option<String>,
+    #[serde(rename = "databaseURL")]
+    #[builder(default, setter(into, strip_option))]
+    pub database_url: Option<String>,
+    #[builder(setter(into))]
+    pub project_id: String,
+    #[builder(default, setter(into, strip_option))]
+    pub storage_bucket: Option<String>,
+    #[builder(default, setter(into, strip_option))]
+    pub messaging_sender_id: Option<String>,
+    #[builder(setter(into))]
+    pub app_id: String,
+    #[builder(default, setter(into, strip_option))]
+    pub measurement_id: Option<String>,
+}
+
+impl FirebaseConfig {
+    /// Reads the config from the `FIREBASE_*` environment variables
+    /// (`FIREBASE_API_KEY`, `FIREBASE_PROJECT_ID`, `FIREBASE_APP_ID`, ...)
+    /// which were set when this crate was compiled.
+    ///
+    /// Fails if `FIREBASE_API_KEY`, `FIREBASE_PROJECT_ID` or
+    /// `FIREBASE_APP_ID` are missing, all other variables are optional.
+    pub fn from_env() -> Result<Self, MissingEnvVarError> {
+        fn required(name: &'static str, value: Option<&str>) -> Result<String, MissingEnvVarError> {
+            value.map(ToOwned::to_owned).ok_or(MissingEnvVarError(name))
+        }
+
+        Ok(Self {
+            api_key: required("FIREBASE_API_KEY", option_env!("FIREBASE_API_KEY"))?,
+            auth_domain: option_env!("FIREBASE_AUTH_DOMAIN").map(ToOwned::to_owned),
+            database_url: option_env!("FIREBASE_DATABASE_URL").map(ToOwned::to_owned),
+            project_id: required("FIREBASE_PROJECT_ID", option_env!("FIREBASE_PROJECT_ID"))?,
+            storage_bucket: option_env!("FIREBASE_STORAGE_BUCKET").map(ToOwned::to_owned),
+            messaging_sender_id: option_env!("FIREBASE_MESSAGING_SENDER_ID").map(ToOwned::to_owned),
+            app_id: required("FIREBASE_APP_ID", option_env!("FIREBASE_APP_ID"))?,
+            measurement_id: option_env!("FIREBASE_MEASUREMENT_ID").map(ToOwned::to_owned),
+        })
+    }
+}
+
+/// Creates a [`FirebaseConfig`].
+///
+/// `firebase_config!()` reads the `FIREBASE_*` environment variables of the
+/// crate invoking the macro at compile time, failing to compile if any of
+/// `FIREBASE_API_KEY`, `FIREBASE_PROJECT_ID` or `FIREBASE_APP_ID` are
+/// missing.
+///
+/// `firebase_config!(api_key = "key", project_id = "proj", ...)` sets the
+/// given fields instead, and fails to compile if a required field is not
+/// provided.
+#[macro_export]
+macro_rules! firebase_config {
+    () => {
+        $crate::app::FirebaseConfig {
+            api_key: ::std::env!(
+                "FIREBASE_API_KEY",
+                "`FIREBASE_API_KEY` must be set to use `firebase_config!()`"
+            )
+            .to_owned(),
+            auth_domain: ::std::option_env!("FIREBASE_AUTH_DOMAIN")
+                .map(::std::borrow::ToOwned::to_owned),
+            database_url: ::std::option_env!("FIREBASE_DATABASE_URL")
+                .map(::std::borrow::ToOwned::to_owned),
+            project_id: ::std::env!(
+                "FIREBASE_PROJECT_ID",
+                "`FIREBASE_PROJECT_ID` must be set to use `firebase_config!()`"
+            )
+            .to_owned(),
+            storage_bucket: ::std::option_env!("FIREBASE_STORAGE_BUCKET")
+                .map(::std::borrow::ToOwned::to_owned),
+            messaging_sender_id: ::std::option_env!("FIREBASE_MESSAGING_SENDER_ID")
+                .map(::std::borrow::ToOwned::to_owned),
+            app_id: ::std::env!(
+                "FIREBASE_APP_ID",
+                "`FIREBASE_APP_ID` must be set to use `firebase_config!()`"
+            )
+            .to_owned(),
+            measurement_id: ::std::option_env!("FIREBASE_MEASUREMENT_ID")
+                .map(::std::borrow::ToOwned::to_owned),
+        }
+    };
+    ($($field:ident = $value:expr),+ $(,)?) => {
+        $crate::app::FirebaseConfig::builder()
+            $(.$field($value))+
+            .build()
+    };
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("environment variable `{0}` was not set at compile time")]
+pub struct MissingEnvVarError(pub &'static str);
+
+pub fn initialize_app(
+    config: &FirebaseConfig,
+    name: Option<&str>,
+) -> Result<FirebaseApp, FirebaseError> {
+    let config = serde_wasm_bindgen::to_value(config).unwrap();
+
+    initialize_app_js(config, name)
+}
+
+#[wasm_bindgen(module = "firebase/app")]
+extern "C" {
+    #[derive(Clone, Debug)]
+    pub type FirebaseApp;
+
+    #[wasm_bindgen(js_name = "getApp")]
+    pub fn get_app() -> FirebaseApp;
+
+    #[wasm_bindgen(js_name = "initializeApp", catch)]
+    fn initialize_app_js(
+        options: JsValue,
+        name: Option<&str>,
+    ) -> Result<FirebaseApp, FirebaseError>;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn name(this: &FirebaseApp) -> String;
+}
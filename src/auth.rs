@@ -1,7 +1,15 @@
+mod multi_factor;
+mod phone;
+mod provider;
+mod streams;
 mod user;
 
-use crate::FirebaseError;
+use crate::{app::FirebaseApp, FirebaseError};
+pub use multi_factor::*;
+pub use phone::*;
+pub use provider::*;
 use std::{error::Error, fmt};
+pub use streams::*;
 pub use user::*;
 use wasm_bindgen::{prelude::*, JsCast};
 
@@ -33,6 +41,14 @@ impl From<FirebaseError> for AuthError {
     }
 }
 
+impl AuthError {
+    /// Creates an error on the Rust side, for mistakes which can be caught
+    /// before calling into the SDK.
+    pub(crate) fn new(code: &str, message: &str) -> Self {
+        FirebaseError::new(code, message).into()
+    }
+}
+
 #[derive(Clone, Debug, strum::EnumString)]
 #[non_exhaustive]
 pub enum AuthErrorKind {
@@ -86,6 +102,8 @@ pub enum AuthErrorKind {
     UnauthorizedContinueUri,
     #[strum(serialize = "auth/expired-action-code")]
     ExpiredActionCode,
+    #[strum(serialize = "auth/multi-factor-auth-required")]
+    MultiFactorAuthRequired,
     #[strum(default)]
     Other(String),
 }
@@ -195,6 +213,82 @@ pub async fn confirm_password_reset(
         .map_err(|err| err.unchecked_into::<FirebaseError>().into())
 }
 
+/// See [`Auth::current_user`].
+pub fn current_user(auth: &Auth) -> Option<User> {
+    auth.current_user()
+}
+
+impl Auth {
+    /// Sends auth emails, such as password resets, in the browser's
+    /// language.
+    pub fn use_device_language(&self) {
+        use_device_language(self);
+    }
+}
+
+/// Where the signed in user is persisted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuthPersistence {
+    /// Persisted in `localStorage`, across tabs and browser restarts.
+    Local,
+    /// Persisted in `sessionStorage`, only for the current tab.
+    Session,
+    /// Not persisted, so the user is signed out when the page reloads.
+    None,
+}
+
+/// Changes where the signed in user is persisted, including the current
+/// user, if any.
+pub async fn set_persistence(auth: Auth, persistence: AuthPersistence) -> Result<(), AuthError> {
+    let persistence = match persistence {
+        AuthPersistence::Local => &*BROWSER_LOCAL_PERSISTENCE,
+        AuthPersistence::Session => &*BROWSER_SESSION_PERSISTENCE,
+        AuthPersistence::None => &*IN_MEMORY_PERSISTENCE,
+    };
+
+    set_persistence_js(auth, persistence)
+        .await
+        .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+}
+
+/// Applies a verification code sent to the user by email, such as when
+/// they click an email verification link.
+pub async fn apply_action_code(auth: Auth, code: &str) -> Result<(), AuthError> {
+    apply_action_code_js(auth, code)
+        .await
+        .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+}
+
+/// Checks a verification code sent to the user by email, without
+/// applying it.
+pub async fn check_action_code(auth: Auth, code: &str) -> Result<ActionCodeInfo, AuthError> {
+    check_action_code_js(auth, code)
+        .await
+        .map(|info| info.unchecked_into::<ActionCodeInfo>())
+        .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+}
+
+/// Gets the sign in methods, such as `password` or `google.com`, which
+/// were previously used with `email`.
+///
+/// This returns an empty list if email enumeration protection is enabled
+/// for the project.
+pub async fn fetch_sign_in_methods_for_email(
+    auth: Auth,
+    email: &str,
+) -> Result<Vec<String>, AuthError> {
+    fetch_sign_in_methods_for_email_js(auth, email)
+        .await
+        .map(|methods| {
+            methods
+                .unchecked_into::<js_sys::Array>()
+                .iter()
+                .filter_map(|method| method.as_string())
+                .collect()
+        })
+        .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+}
+
 pub async fn update_email(user: &User, email: String) -> Result<(), JsValue> {
     update_email_js(user, email)
         .await
@@ -210,17 +304,90 @@ pub struct UserCredential {
     pub operation_type: String,
 }
 
+#[wasm_bindgen_struct]
+#[opts(module = "firebase/auth", getter)]
+#[derive(Clone, Debug)]
+pub struct ActionCodeInfo {
+    /// The kind of action, such as `VERIFY_EMAIL` or `PASSWORD_RESET`.
+    pub operation: String,
+    pub data: ActionCodeData,
+}
+
+#[wasm_bindgen_struct]
+#[opts(module = "firebase/auth", getter)]
+#[derive(Clone, Debug)]
+pub struct ActionCodeData {
+    pub email: Option<String>,
+    pub previous_email: Option<String>,
+    pub multi_factor_info: Option<MultiFactorInfo>,
+}
+
 #[wasm_bindgen(module = "firebase/auth")]
 extern "C" {
     #[derive(Clone, Debug)]
     pub type Auth;
+    #[derive(Clone, Debug)]
+    type Persistence;
+
+    #[wasm_bindgen(js_name = browserLocalPersistence)]
+    static BROWSER_LOCAL_PERSISTENCE: Persistence;
+
+    #[wasm_bindgen(js_name = browserSessionPersistence)]
+    static BROWSER_SESSION_PERSISTENCE: Persistence;
+
+    #[wasm_bindgen(js_name = inMemoryPersistence)]
+    static IN_MEMORY_PERSISTENCE: Persistence;
+
+    #[wasm_bindgen(js_name = useDeviceLanguage)]
+    fn use_device_language(auth: &Auth);
+
+    /// The language auth emails are sent in, or `None` for the project's
+    /// default language.
+    #[wasm_bindgen(method, getter, js_name = languageCode)]
+    pub fn language_code(this: &Auth) -> Option<String>;
+
+    #[wasm_bindgen(method, setter, js_name = languageCode)]
+    pub fn set_language_code(this: &Auth, code: Option<&str>);
+
+    /// The signed in user, or `None` if nobody is signed in.
+    ///
+    /// This is also `None` until a persisted user has been restored after
+    /// startup, which [`auth_state_stream`] reports.
+    #[wasm_bindgen(method, getter, js_name = currentUser)]
+    pub fn current_user(this: &Auth) -> Option<User>;
+
+    /// The Identity Platform tenant which sign in operations are scoped
+    /// to, or `None` for the project-level tenant.
+    #[wasm_bindgen(method, getter, js_name = tenantId)]
+    pub fn tenant_id(this: &Auth) -> Option<String>;
+
+    #[wasm_bindgen(method, setter, js_name = tenantId)]
+    pub fn set_tenant_id(this: &Auth, id: Option<&str>);
+
+    #[wasm_bindgen(js_name = setPersistence, catch)]
+    async fn set_persistence_js(auth: Auth, persistence: &Persistence) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(js_name = getAuth)]
+    pub fn get_auth(app: &FirebaseApp) -> Auth;
 
     #[wasm_bindgen(js_name = getAuth)]
-    pub fn get_auth() -> Auth;
+    pub fn get_auth_default() -> Auth;
 
     #[wasm_bindgen(js_name = onAuthStateChanged)]
     pub fn on_auth_state_changed(auth: Auth, callback: &Closure<dyn FnMut(Option<User>)>);
 
+    #[wasm_bindgen(js_name = onAuthStateChanged)]
+    fn on_auth_state_changed_unsub(
+        auth: Auth,
+        callback: &Closure<dyn FnMut(Option<User>)>,
+    ) -> js_sys::Function;
+
+    #[wasm_bindgen(js_name = onIdTokenChanged)]
+    fn on_id_token_changed(
+        auth: Auth,
+        callback: &Closure<dyn FnMut(Option<User>)>,
+    ) -> js_sys::Function;
+
     #[wasm_bindgen(js_name = createUserWithEmailAndPassword, catch)]
     async fn create_user_with_email_and_password_js(
         auth: Auth,
@@ -272,6 +439,18 @@ extern "C" {
         new_password: String,
     ) -> Result<(), JsValue>;
 
+    #[wasm_bindgen(js_name = applyActionCode, catch)]
+    async fn apply_action_code_js(auth: Auth, code: &str) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(js_name = checkActionCode, catch)]
+    async fn check_action_code_js(auth: Auth, code: &str) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_name = fetchSignInMethodsForEmail, catch)]
+    async fn fetch_sign_in_methods_for_email_js(
+        auth: Auth,
+        email: &str,
+    ) -> Result<JsValue, JsValue>;
+
     #[wasm_bindgen(js_name = updateEmail, catch)]
     async fn update_email_js(user: &User, new_email: String) -> Result<(), JsValue>;
 }
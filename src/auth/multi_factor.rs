@@ -0,0 +1,142 @@
+use super::{Auth, AuthError, AuthErrorKind, User, UserCredential};
+use crate::FirebaseError;
+use wasm_bindgen::{prelude::*, JsCast};
+
+/// Gets a resolver for completing sign in with a second factor, if
+/// `error` is an [`AuthErrorKind::MultiFactorAuthRequired`] error.
+pub fn get_multi_factor_resolver(auth: Auth, error: &AuthError) -> Option<MultiFactorResolver> {
+    if !matches!(error.kind, AuthErrorKind::MultiFactorAuthRequired) {
+        return None;
+    }
+
+    get_multi_factor_resolver_js(auth, &error.source).ok()
+}
+
+impl MultiFactorResolver {
+    /// Completes sign in with the second factor proven by `assertion`.
+    pub async fn resolve_sign_in(
+        &self,
+        assertion: MultiFactorAssertion,
+    ) -> Result<UserCredential, AuthError> {
+        self.resolve_sign_in_js(assertion)
+            .await
+            .map(|cred| cred.unchecked_into::<UserCredential>())
+            .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+    }
+}
+
+impl User {
+    pub fn multi_factor(&self) -> MultiFactorUser {
+        multi_factor(self)
+    }
+}
+
+impl MultiFactorUser {
+    /// Enrolls the second factor proven by `assertion` for the user.
+    pub async fn enroll(
+        &self,
+        assertion: MultiFactorAssertion,
+        display_name: Option<&str>,
+    ) -> Result<(), AuthError> {
+        self.enroll_js(assertion, display_name)
+            .await
+            .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+    }
+
+    /// Gets a session for enrolling a new second factor.
+    pub async fn get_session(&self) -> Result<MultiFactorSession, AuthError> {
+        self.get_session_js()
+            .await
+            .map(|session| session.unchecked_into::<MultiFactorSession>())
+            .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+    }
+
+    /// Unenrolls the second factor with the given `factor_uid`.
+    pub async fn unenroll(&self, factor_uid: &str) -> Result<(), AuthError> {
+        self.unenroll_js(factor_uid)
+            .await
+            .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+    }
+}
+
+#[wasm_bindgen(module = "firebase/auth")]
+extern "C" {
+    #[derive(Clone, Debug)]
+    pub type MultiFactorResolver;
+    #[derive(Clone, Debug)]
+    pub type MultiFactorUser;
+    #[derive(Clone, Debug)]
+    pub type MultiFactorAssertion;
+    #[derive(Clone, Debug)]
+    pub type MultiFactorSession;
+    #[derive(Clone, Debug)]
+    pub type MultiFactorInfo;
+
+    #[wasm_bindgen(js_name = getMultiFactorResolver, catch)]
+    fn get_multi_factor_resolver_js(
+        auth: Auth,
+        error: &FirebaseError,
+    ) -> Result<MultiFactorResolver, JsValue>;
+
+    #[wasm_bindgen(js_name = multiFactor)]
+    fn multi_factor(user: &User) -> MultiFactorUser;
+
+    // =========================================================================
+    //                            MultiFactorResolver
+    // =========================================================================
+
+    #[wasm_bindgen(method, getter)]
+    pub fn hints(this: &MultiFactorResolver) -> Vec<MultiFactorInfo>;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn session(this: &MultiFactorResolver) -> MultiFactorSession;
+
+    #[wasm_bindgen(method, js_name = resolveSignIn, catch)]
+    async fn resolve_sign_in_js(
+        this: &MultiFactorResolver,
+        assertion: MultiFactorAssertion,
+    ) -> Result<JsValue, JsValue>;
+
+    // =========================================================================
+    //                            MultiFactorUser
+    // =========================================================================
+
+    #[wasm_bindgen(method, getter, js_name = enrolledFactors)]
+    pub fn enrolled_factors(this: &MultiFactorUser) -> Vec<MultiFactorInfo>;
+
+    #[wasm_bindgen(method, js_name = enroll, catch)]
+    async fn enroll_js(
+        this: &MultiFactorUser,
+        assertion: MultiFactorAssertion,
+        display_name: Option<&str>,
+    ) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(method, js_name = getSession, catch)]
+    async fn get_session_js(this: &MultiFactorUser) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(method, js_name = unenroll, catch)]
+    async fn unenroll_js(this: &MultiFactorUser, factor_uid: &str) -> Result<(), JsValue>;
+
+    // =========================================================================
+    //                            MultiFactorAssertion
+    // =========================================================================
+
+    #[wasm_bindgen(method, getter, js_name = factorId)]
+    pub fn factor_id(this: &MultiFactorAssertion) -> String;
+
+    // =========================================================================
+    //                            MultiFactorInfo
+    // =========================================================================
+
+    #[wasm_bindgen(method, getter)]
+    pub fn uid(this: &MultiFactorInfo) -> String;
+
+    #[wasm_bindgen(method, getter, js_name = displayName)]
+    pub fn display_name(this: &MultiFactorInfo) -> Option<String>;
+
+    #[wasm_bindgen(method, getter, js_name = enrollmentTime)]
+    pub fn enrollment_time(this: &MultiFactorInfo) -> String;
+
+    #[wasm_bindgen(method, getter, js_name = factorId)]
+    pub fn factor_id(this: &MultiFactorInfo) -> String;
+}
@@ -0,0 +1,135 @@
+use super::{Auth, AuthError, UserCredential};
+use crate::FirebaseError;
+use wasm_bindgen::{prelude::*, JsCast};
+
+impl PhoneAuthProvider {
+    /// Sends an SMS with a verification code to `phone_number`, returning
+    /// the verification ID to pass to [`PhoneAuthProvider::credential`].
+    pub async fn verify_phone_number(
+        &self,
+        phone_number: &str,
+        app_verifier: &ApplicationVerifier,
+    ) -> Result<String, AuthError> {
+        self.verify_phone_number_js(phone_number, app_verifier)
+            .await
+            .map(|id| id.as_string().expect("verification ID to be a string"))
+            .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+    }
+}
+
+impl RecaptchaVerifier {
+    /// Creates an invisible reCAPTCHA, which is attached to the element
+    /// with the ID `container_id`, usually the sign in button.
+    pub fn new_invisible(container_id: &str, auth: Auth) -> Self {
+        let parameters = js_sys::Object::new();
+
+        js_sys::Reflect::set(&parameters, &"size".into(), &"invisible".into()).unwrap();
+
+        Self::new_js(auth, container_id, &parameters)
+    }
+
+    /// Waits for the reCAPTCHA to be solved, returning its response token.
+    pub async fn verify(&self) -> Result<String, AuthError> {
+        self.verify_js()
+            .await
+            .map(|token| token.as_string().expect("reCAPTCHA token to be a string"))
+            .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+    }
+}
+
+/// Same as [`PhoneAuthProvider::verify_phone_number`].
+pub async fn verify_phone_number(
+    provider: &PhoneAuthProvider,
+    phone_number: &str,
+    app_verifier: &ApplicationVerifier,
+) -> Result<String, AuthError> {
+    provider
+        .verify_phone_number(phone_number, app_verifier)
+        .await
+}
+
+/// Signs in with a credential, such as a [`PhoneAuthCredential`].
+pub async fn sign_in_with_credential(
+    auth: Auth,
+    credential: &AuthCredential,
+) -> Result<UserCredential, AuthError> {
+    sign_in_with_credential_js(auth, credential)
+        .await
+        .map(|cred| cred.unchecked_into::<UserCredential>())
+        .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+}
+
+#[wasm_bindgen(module = "firebase/auth")]
+extern "C" {
+    #[derive(Clone, Debug)]
+    pub type AuthCredential;
+    #[wasm_bindgen(extends = AuthCredential)]
+    #[derive(Clone, Debug)]
+    pub type PhoneAuthCredential;
+    #[derive(Clone, Debug)]
+    pub type PhoneAuthProvider;
+    /// Proves that the user is not a bot, such as a [`RecaptchaVerifier`].
+    #[derive(Clone, Debug)]
+    pub type ApplicationVerifier;
+    #[wasm_bindgen(extends = ApplicationVerifier)]
+    #[derive(Clone, Debug)]
+    pub type RecaptchaVerifier;
+
+    #[wasm_bindgen(js_name = signInWithCredential, catch)]
+    async fn sign_in_with_credential_js(
+        auth: Auth,
+        credential: &AuthCredential,
+    ) -> Result<JsValue, JsValue>;
+
+    // =========================================================================
+    //                            AuthCredential
+    // =========================================================================
+
+    #[wasm_bindgen(method, getter, js_name = providerId)]
+    pub fn provider_id(this: &AuthCredential) -> String;
+
+    #[wasm_bindgen(method, getter, js_name = signInMethod)]
+    pub fn sign_in_method(this: &AuthCredential) -> String;
+
+    // =========================================================================
+    //                            PhoneAuthProvider
+    // =========================================================================
+
+    #[wasm_bindgen(constructor)]
+    pub fn new(auth: Auth) -> PhoneAuthProvider;
+
+    /// Creates a credential from the verification ID returned by
+    /// [`PhoneAuthProvider::verify_phone_number`] and the code the user
+    /// received.
+    #[wasm_bindgen(static_method_of = PhoneAuthProvider)]
+    pub fn credential(verification_id: &str, verification_code: &str) -> PhoneAuthCredential;
+
+    #[wasm_bindgen(method, js_name = verifyPhoneNumber, catch)]
+    async fn verify_phone_number_js(
+        this: &PhoneAuthProvider,
+        phone_number: &str,
+        app_verifier: &ApplicationVerifier,
+    ) -> Result<JsValue, JsValue>;
+
+    // =========================================================================
+    //                            ApplicationVerifier
+    // =========================================================================
+
+    #[wasm_bindgen(method, getter, js_name = type)]
+    pub fn type_(this: &ApplicationVerifier) -> String;
+
+    // =========================================================================
+    //                            RecaptchaVerifier
+    // =========================================================================
+
+    // This is the argument order since SDK v10, v9 took `auth` last
+    #[wasm_bindgen(constructor)]
+    fn new_js(auth: Auth, container_id: &str, parameters: &js_sys::Object) -> RecaptchaVerifier;
+
+    /// Removes the reCAPTCHA widget from the page.
+    #[wasm_bindgen(method)]
+    pub fn clear(this: &RecaptchaVerifier);
+
+    #[wasm_bindgen(method, js_name = verify, catch)]
+    async fn verify_js(this: &RecaptchaVerifier) -> Result<JsValue, JsValue>;
+}
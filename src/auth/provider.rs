@@ -0,0 +1,176 @@
+use super::{Auth, AuthError, User, UserCredential};
+use crate::FirebaseError;
+use wasm_bindgen::{prelude::*, JsCast};
+
+impl SamlAuthProvider {
+    /// Creates a provider for the SAML provider `provider_id`, which must
+    /// start with `saml.`.
+    pub fn new(provider_id: &str) -> Result<Self, AuthError> {
+        validate_provider_id(provider_id, "saml.")?;
+
+        Ok(Self::new_js(provider_id))
+    }
+}
+
+impl OidcAuthProvider {
+    /// Creates a provider for the OIDC provider `provider_id`, which must
+    /// start with `oidc.`.
+    pub fn new(provider_id: &str) -> Result<Self, AuthError> {
+        validate_provider_id(provider_id, "oidc.")?;
+
+        Ok(Self::new_js(provider_id))
+    }
+
+    /// Requests an additional OAuth scope from the provider.
+    pub fn add_scope(&self, scope: &str) {
+        self.add_scope_js(scope);
+    }
+}
+
+impl AuthProvider {
+    /// Sets custom OAuth parameters to pass along with sign in requests.
+    pub fn set_custom_parameters(&self, parameters: &[(&str, &str)]) {
+        let parameters_obj = js_sys::Object::new();
+
+        for &(key, value) in parameters {
+            js_sys::Reflect::set(&parameters_obj, &key.into(), &value.into()).unwrap();
+        }
+
+        self.set_custom_parameters_js(&parameters_obj);
+    }
+}
+
+fn validate_provider_id(provider_id: &str, prefix: &str) -> Result<(), AuthError> {
+    if provider_id.starts_with(prefix) {
+        Ok(())
+    } else {
+        Err(AuthError::new(
+            "auth/argument-error",
+            &format!("provider ID `{provider_id}` must start with `{prefix}`"),
+        ))
+    }
+}
+
+/// Signs in with `provider` in a popup window.
+pub async fn sign_in_with_popup(
+    auth: Auth,
+    provider: &AuthProvider,
+) -> Result<UserCredential, AuthError> {
+    sign_in_with_popup_js(auth, provider)
+        .await
+        .map(|cred| cred.unchecked_into::<UserCredential>())
+        .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+}
+
+/// Signs in with `provider` by navigating away from the page.
+pub async fn sign_in_with_redirect(auth: Auth, provider: &AuthProvider) -> Result<(), AuthError> {
+    sign_in_with_redirect_js(auth, provider)
+        .await
+        .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+}
+
+/// Gets the result of signing in or linking with a redirect, once the
+/// user has been sent back to the page, or `None` if there was no
+/// redirect.
+pub async fn get_redirect_result(auth: Auth) -> Result<Option<UserCredential>, AuthError> {
+    get_redirect_result_js(auth)
+        .await
+        .map(|cred| {
+            (!cred.is_null() && !cred.is_undefined())
+                .then(|| cred.unchecked_into::<UserCredential>())
+        })
+        .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+}
+
+/// Links `provider` to `user` in a popup window, so that they can sign in
+/// with either.
+pub async fn link_with_popup(
+    user: User,
+    provider: &AuthProvider,
+) -> Result<UserCredential, AuthError> {
+    link_with_popup_js(user, provider)
+        .await
+        .map(|cred| cred.unchecked_into::<UserCredential>())
+        .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+}
+
+/// Links `provider` to `user` by navigating away from the page. The
+/// result is available from [`get_redirect_result`] once the user is sent
+/// back.
+pub async fn link_with_redirect(user: User, provider: &AuthProvider) -> Result<(), AuthError> {
+    link_with_redirect_js(user, provider)
+        .await
+        .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+}
+
+/// Removes the provider `provider_id`, such as `google.com`, from
+/// `user`, returning the updated user.
+///
+/// The user's other providers are listed in [`User::provider_data`]. At
+/// least one of them has to remain, or the user could no longer sign in.
+pub async fn unlink_provider(user: User, provider_id: &str) -> Result<User, AuthError> {
+    unlink_js(user, provider_id)
+        .await
+        .map(|user| user.unchecked_into::<User>())
+        .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+}
+
+#[wasm_bindgen(module = "firebase/auth")]
+extern "C" {
+    #[derive(Clone, Debug)]
+    pub type AuthProvider;
+    #[wasm_bindgen(extends = AuthProvider, js_name = SAMLAuthProvider)]
+    #[derive(Clone, Debug)]
+    pub type SamlAuthProvider;
+    /// SAML providers do not support OAuth scopes, so only this has
+    /// [`add_scope`](OidcAuthProvider::add_scope).
+    #[wasm_bindgen(extends = AuthProvider, js_name = OAuthProvider)]
+    #[derive(Clone, Debug)]
+    pub type OidcAuthProvider;
+
+    #[wasm_bindgen(js_name = signInWithPopup, catch)]
+    async fn sign_in_with_popup_js(auth: Auth, provider: &AuthProvider)
+        -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_name = signInWithRedirect, catch)]
+    async fn sign_in_with_redirect_js(auth: Auth, provider: &AuthProvider) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(js_name = getRedirectResult, catch)]
+    async fn get_redirect_result_js(auth: Auth) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_name = linkWithPopup, catch)]
+    async fn link_with_popup_js(user: User, provider: &AuthProvider) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_name = linkWithRedirect, catch)]
+    async fn link_with_redirect_js(user: User, provider: &AuthProvider) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(js_name = unlink, catch)]
+    async fn unlink_js(user: User, provider_id: &str) -> Result<JsValue, JsValue>;
+
+    // =========================================================================
+    //                            AuthProvider
+    // =========================================================================
+
+    #[wasm_bindgen(method, getter, js_name = providerId)]
+    pub fn provider_id(this: &AuthProvider) -> String;
+
+    #[wasm_bindgen(method, js_name = setCustomParameters)]
+    fn set_custom_parameters_js(this: &AuthProvider, parameters: &js_sys::Object);
+
+    // =========================================================================
+    //                            SamlAuthProvider
+    // =========================================================================
+
+    #[wasm_bindgen(constructor, js_class = "SAMLAuthProvider")]
+    fn new_js(provider_id: &str) -> SamlAuthProvider;
+
+    // =========================================================================
+    //                            OidcAuthProvider
+    // =========================================================================
+
+    #[wasm_bindgen(constructor, js_class = "OAuthProvider")]
+    fn new_js(provider_id: &str) -> OidcAuthProvider;
+
+    #[wasm_bindgen(method, js_class = "OAuthProvider", js_name = addScope)]
+    fn add_scope_js(this: &OidcAuthProvider, scope: &str);
+}
@@ -0,0 +1,77 @@
+use super::{on_auth_state_changed_unsub, on_id_token_changed, Auth, User};
+use futures::Stream;
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+use wasm_bindgen::prelude::*;
+
+/// Yields the signed in user whenever someone signs in or out, starting
+/// with the current user.
+pub fn auth_state_stream(auth: Auth) -> UserStream {
+    UserStream::new(|callback| on_auth_state_changed_unsub(auth, callback))
+}
+
+/// Same as [`auth_state_stream`], but also yields whenever the user's ID
+/// token is refreshed, which happens about every hour.
+///
+/// Use this to keep ID tokens which are sent to a backend up to date.
+pub fn id_token_stream(auth: Auth) -> UserStream {
+    UserStream::new(|callback| on_id_token_changed(auth, callback))
+}
+
+/// A never-ending [`Stream`] of the signed in user, or `None` while
+/// signed out.
+pub struct UserStream {
+    _on_change: Closure<dyn FnMut(Option<User>)>,
+    users: Rc<RefCell<VecDeque<Option<User>>>>,
+    waker: Rc<RefCell<Option<Waker>>>,
+    unsub: js_sys::Function,
+}
+
+impl UserStream {
+    fn new(subscribe: impl FnOnce(&Closure<dyn FnMut(Option<User>)>) -> js_sys::Function) -> Self {
+        let waker: Rc<RefCell<Option<Waker>>> = Rc::default();
+        let users: Rc<RefCell<VecDeque<Option<User>>>> = Rc::default();
+
+        let on_change = Closure::new(clone!([users, waker], move |user: Option<User>| {
+            users.borrow_mut().push_back(user);
+
+            if let Some(w) = waker.borrow().as_ref() {
+                w.wake_by_ref();
+            }
+        }));
+
+        let unsub = subscribe(&on_change);
+
+        Self {
+            _on_change: on_change,
+            users,
+            waker,
+            unsub,
+        }
+    }
+}
+
+impl Drop for UserStream {
+    fn drop(&mut self) {
+        let _ = self.unsub.call0(&JsValue::UNDEFINED);
+    }
+}
+
+impl Stream for UserStream {
+    type Item = Option<User>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // Update waker
+        *self.waker.borrow_mut() = Some(cx.waker().to_owned());
+
+        match self.users.borrow_mut().pop_front() {
+            Some(user) => Poll::Ready(Some(user)),
+            None => Poll::Pending,
+        }
+    }
+}
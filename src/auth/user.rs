@@ -1,6 +1,7 @@
 use super::AuthError;
 use crate::FirebaseError;
 use serde::Deserialize;
+use std::fmt;
 use wasm_bindgen::{prelude::*, JsCast};
 
 impl ParsedToken {
@@ -71,7 +72,7 @@ pub struct ParsedToken {
 
 #[wasm_bindgen_struct]
 #[opts(module = "firebase/auth", getter, extends = UserInfo)]
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 #[wasm_bindgen(typescript_type = r#"import("firebase/auth").User"#)]
 pub struct User {
     pub email_verified: bool,
@@ -82,6 +83,24 @@ pub struct User {
     pub tenant_id: String,
 }
 
+impl fmt::Debug for User {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("User")
+            .field("uid", &self.uid())
+            .field("email", &self.email())
+            .finish()
+    }
+}
+
+/// Users are the same if they have the same `uid`, even if one of them
+/// has since been reloaded.
+impl PartialEq for User {
+    fn eq(&self, other: &Self) -> bool {
+        self.uid() == other.uid()
+    }
+}
+impl Eq for User {}
+
 #[wasm_bindgen_struct]
 #[opts(module = "firebase/auth")]
 impl User {
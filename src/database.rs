@@ -0,0 +1,118 @@
+use crate::app::FirebaseApp;
+use wasm_bindgen::{prelude::*, JsCast};
+
+/// An error from the Realtime Database.
+///
+/// Unlike the other products, the Realtime Database rejects with plain JS
+/// errors, whose messages start with a code such as `PERMISSION_DENIED`.
+#[derive(Clone, Debug, thiserror::Error)]
+#[error("{message}")]
+pub struct RtdbError {
+    /// The `code` of the error, if it had one.
+    pub code: Option<String>,
+    pub message: String,
+}
+
+impl RtdbError {
+    fn from_js_error(err: JsValue) -> Self {
+        let code = js_sys::Reflect::get(&err, &"code".into())
+            .ok()
+            .and_then(|code| code.as_string());
+
+        let message = err
+            .dyn_ref::<js_sys::Error>()
+            .map(|err| String::from(err.message()))
+            .or_else(|| err.as_string())
+            .unwrap_or_else(|| format!("{err:?}"));
+
+        Self { code, message }
+    }
+}
+
+/// Writes `value` to `ref_`, replacing anything already there.
+pub async fn set(ref_: DatabaseRef, value: impl Into<JsValue>) -> Result<(), RtdbError> {
+    set_js(ref_, value.into())
+        .await
+        .map_err(RtdbError::from_js_error)
+}
+
+impl OnDisconnect {
+    /// Writes `value` once the client disconnects, such as to mark the user
+    /// as offline.
+    pub async fn set(&self, value: impl Into<JsValue>) -> Result<(), RtdbError> {
+        self.set_js(value.into())
+            .await
+            .map_err(RtdbError::from_js_error)
+    }
+
+    /// Writes the children in `values` once the client disconnects,
+    /// leaving other children as they are.
+    pub async fn update(&self, values: impl Into<JsValue>) -> Result<(), RtdbError> {
+        self.update_js(values.into())
+            .await
+            .map_err(RtdbError::from_js_error)
+    }
+
+    /// Removes the data once the client disconnects.
+    pub async fn remove(&self) -> Result<(), RtdbError> {
+        self.remove_js().await.map_err(RtdbError::from_js_error)
+    }
+
+    /// Cancels every write queued for this location, and all of its
+    /// children, by this client.
+    pub async fn cancel(&self) -> Result<(), RtdbError> {
+        self.cancel_js().await.map_err(RtdbError::from_js_error)
+    }
+}
+
+#[wasm_bindgen(module = "firebase/database")]
+extern "C" {
+    #[derive(Clone, Debug)]
+    pub type Database;
+    #[derive(Clone, Debug)]
+    pub type DatabaseRef;
+    /// Writes which the server makes once this client disconnects, for
+    /// presence tracking. They are queued as soon as they are acknowledged,
+    /// so they happen even if the page is closed.
+    #[derive(Clone, Debug)]
+    pub type OnDisconnect;
+
+    #[wasm_bindgen(js_name = getDatabase)]
+    pub fn get_database(app: &FirebaseApp, url: Option<&str>) -> Database;
+
+    #[wasm_bindgen(js_name = getDatabase)]
+    pub fn get_database_default() -> Database;
+
+    #[wasm_bindgen(js_name = ref)]
+    pub fn ref_(database: Database, path: &str) -> DatabaseRef;
+
+    #[wasm_bindgen(js_name = onDisconnect)]
+    pub fn on_disconnect(ref_: DatabaseRef) -> OnDisconnect;
+
+    #[wasm_bindgen(js_name = set, catch)]
+    async fn set_js(ref_: DatabaseRef, value: JsValue) -> Result<(), JsValue>;
+
+    // =========================================================================
+    //                            DatabaseRef
+    // =========================================================================
+
+    /// The last segment of the path, or `None` for the root.
+    #[wasm_bindgen(method, getter)]
+    pub fn key(this: &DatabaseRef) -> Option<String>;
+
+    // =========================================================================
+    //                            OnDisconnect
+    // =========================================================================
+
+    #[wasm_bindgen(method, js_name = set, catch)]
+    async fn set_js(this: &OnDisconnect, value: JsValue) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(method, js_name = update, catch)]
+    async fn update_js(this: &OnDisconnect, values: JsValue) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(method, js_name = remove, catch)]
+    async fn remove_js(this: &OnDisconnect) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(method, js_name = cancel, catch)]
+    async fn cancel_js(this: &OnDisconnect) -> Result<(), JsValue>;
+}
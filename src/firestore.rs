@@ -1,15 +1,48 @@
+mod batch;
 mod bindings;
+mod converter;
+mod cursor;
+mod data;
+mod metrics;
+mod paged_query;
+mod query_builder;
+mod retry;
+mod snapshots;
+mod typed;
+mod update;
 
 use crate::FirebaseError;
+pub use batch::*;
 use bindings as b;
 pub use bindings::{
-    add_doc, delete_doc, doc, get_firestore, limit, on_snapshot_doc, on_snapshot_query, query,
-    set_doc, start_after, update_doc, CollectionReference, DocumentReference, DocumentSnapshot,
-    Firestore, Query, QueryConstraint, QuerySnapshot, SetDocOptions, Timestamp, Transaction,
-    server_timestamp
+    doc_auto_id, get_firestore, get_firestore_default, get_persistent_cache_index_manager, limit,
+    on_snapshot_doc, on_snapshot_query, query, server_timestamp, start_after, start_after_snapshot,
+    Bytes, CollectionReference, DocumentChange, DocumentReference, DocumentSnapshot, FieldPath,
+    FieldValue, Firestore, GeoPoint, LoadBundleTask, LoadBundleTaskProgress,
+    PersistentCacheIndexManager, Query, QueryConstraint, QuerySnapshot, SnapshotMetadata,
+    Timestamp, Transaction, VectorValue,
 };
-use futures::Future;
-use std::{cell::RefCell, error::Error, fmt, rc::Rc, time::SystemTime};
+pub use converter::*;
+pub use cursor::*;
+use data::to_value;
+pub use data::*;
+use futures::{Future, StreamExt, TryStreamExt};
+pub use metrics::{clear_metrics_sink, set_metrics_sink, OpKind, OpMetric};
+pub use paged_query::*;
+pub use query_builder::*;
+pub use retry::*;
+use serde::{de::DeserializeOwned, Serialize};
+pub use snapshots::*;
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashSet,
+    error::Error,
+    fmt,
+    rc::Rc,
+    time::SystemTime,
+};
+pub use typed::*;
+pub use update::*;
 use wasm_bindgen::{
     prelude::{Closure, *},
     JsCast, JsValue,
@@ -39,13 +72,34 @@ impl Error for FirestoreError {
 
 impl From<FirebaseError> for FirestoreError {
     fn from(err: FirebaseError) -> Self {
-        let kind = err.code().parse().unwrap();
+        let kind = FirestoreErrorKind::from_code(&err.code());
 
         Self { kind, source: err }
     }
 }
 
-#[derive(Clone, Debug, strum::EnumString)]
+impl FirestoreError {
+    /// Creates an error on the Rust side, for mistakes which can be caught
+    /// before calling into the SDK.
+    pub(crate) fn new(code: &str, message: &str) -> Self {
+        FirebaseError::new(code, message).into()
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        self.kind == FirestoreErrorKind::NotFound
+    }
+
+    pub fn is_permission_denied(&self) -> bool {
+        self.kind == FirestoreErrorKind::PermissionDenied
+    }
+
+    /// See [`FirestoreErrorKind::is_retryable`].
+    pub fn is_retryable(&self) -> bool {
+        self.kind.is_retryable()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, strum::EnumString, strum::IntoStaticStr)]
 #[non_exhaustive]
 pub enum FirestoreErrorKind {
     #[strum(serialize = "cancelled")]
@@ -80,8 +134,52 @@ pub enum FirestoreErrorKind {
     DataLoss,
     #[strum(serialize = "unauthenticated")]
     Unauthenticated,
+    /// Data could not be serialized before being sent to Firestore. This
+    /// never comes from the SDK.
+    #[strum(serialize = "serialization-error")]
+    SerializationError,
+    /// A code which is not known to this crate.
     #[strum(default)]
-    Other(String),
+    Unrecognized(String),
+}
+
+impl FirestoreErrorKind {
+    /// Parses an error code, with or without the `firestore/` service
+    /// prefix. [`Unrecognized`](Self::Unrecognized) codes are kept without
+    /// the prefix, but the raw code is still available through
+    /// [`FirestoreError::source`].
+    pub fn from_code(code: &str) -> Self {
+        let unprefixed = code.rsplit_once('/').map_or(code, |(_, code)| code);
+
+        match unprefixed.parse() {
+            Ok(kind) => kind,
+            // Unreachable, since unknown codes parse to the default variant
+            Err(_) => Self::Unrecognized(unprefixed.to_owned()),
+        }
+    }
+
+    pub fn is_unrecognized(&self) -> bool {
+        matches!(self, Self::Unrecognized(_))
+    }
+
+    /// Whether the operation might succeed if retried, possibly after a
+    /// backoff.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::Unavailable | Self::DeadlineExceeded | Self::Aborted | Self::ResourceExhausted
+        )
+    }
+}
+
+impl fmt::Display for FirestoreErrorKind {
+    /// Writes the error code, such as `permission-denied`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unrecognized(code) => f.write_str(code),
+            kind => f.write_str(<&'static str>::from(kind)),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -90,9 +188,43 @@ pub enum Direction {
     Desc,
 }
 
+/// Creates a [`FieldPath`] from its individual field names, which may
+/// themselves contain dots.
+pub fn field_path(field_names: &[&str]) -> FieldPath {
+    FieldPath::new_js(
+        field_names
+            .iter()
+            .map(|&field_name| JsValue::from(field_name))
+            .collect(),
+    )
+}
+
+/// Gets the special [`FieldPath`] which refers to a document's ID.
+pub fn document_id_path() -> FieldPath {
+    b::document_id()
+}
+
+impl From<&str> for FieldPath {
+    /// Parses a dot-separated field path, such as `address.city`, the same
+    /// way the JS SDK does for string paths.
+    fn from(path: &str) -> Self {
+        field_path(&path.split('.').collect::<Vec<_>>())
+    }
+}
+
+impl From<String> for FieldPath {
+    fn from(path: String) -> Self {
+        path.as_str().into()
+    }
+}
+
 pub fn order_by(field_path: &str, direction: Direction) -> QueryConstraint {
+    order_by_field_path(field_path.into(), direction)
+}
+
+pub fn order_by_field_path(field_path: FieldPath, direction: Direction) -> QueryConstraint {
     b::order_by(
-        field_path,
+        &field_path,
         match direction {
             Direction::Asc => "asc",
             Direction::Desc => "desc",
@@ -100,14 +232,63 @@ pub fn order_by(field_path: &str, direction: Direction) -> QueryConstraint {
     )
 }
 
-pub fn where_<V: Into<JsValue>>(
-    field_path: &str,
+pub fn where_<F: Into<FieldPath>, V: Into<JsValue>>(
+    field_path: F,
     op: QueryConstraintOp,
     value: V,
 ) -> QueryConstraint {
     let value = value.into();
 
-    b::where_(field_path, &op.to_string(), value)
+    b::where_(&field_path.into(), &op.to_string(), value)
+}
+
+/// Matches documents where `field_path` is equal to any of `values`.
+pub fn where_in<F: Into<FieldPath>, V: Into<JsValue> + Clone>(
+    field_path: F,
+    values: &[V],
+) -> QueryConstraint {
+    where_(field_path, QueryConstraintOp::In, to_js_array(values))
+}
+
+/// Matches documents where `field_path` is not equal to any of `values`.
+pub fn where_not_in<F: Into<FieldPath>, V: Into<JsValue> + Clone>(
+    field_path: F,
+    values: &[V],
+) -> QueryConstraint {
+    where_(field_path, QueryConstraintOp::NotIn, to_js_array(values))
+}
+
+/// Matches documents where the array `field_path` contains any of
+/// `values`.
+///
+/// Firestore accepts at most 30 values here (older releases only allowed
+/// 10). See [`get_docs_where_in`] for a way around this limit for `in`
+/// filters.
+pub fn where_array_contains_any<F: Into<FieldPath>, V: Into<JsValue> + Clone>(
+    field_path: F,
+    values: &[V],
+) -> QueryConstraint {
+    where_(
+        field_path,
+        QueryConstraintOp::ArrayContainsAny,
+        to_js_array(values),
+    )
+}
+
+fn to_js_array<V: Into<JsValue> + Clone>(values: &[V]) -> js_sys::Array {
+    values.iter().cloned().map(Into::<JsValue>::into).collect()
+}
+
+/// Creates a composite filter which matches documents matching any of
+/// the given `constraints`.
+pub fn or_(constraints: &[QueryConstraint]) -> QueryConstraint {
+    b::or(constraints.to_vec())
+}
+
+/// Creates a composite filter which matches documents matching all of
+/// the given `constraints`.
+pub fn and_(constraints: &[QueryConstraint]) -> QueryConstraint {
+    b::and(constraints.to_vec())
 }
 
 pub enum QueryConstraintOp {
@@ -152,32 +333,448 @@ impl fmt::Display for QueryConstraintOp {
     }
 }
 
-pub async fn get_doc(doc: DocumentReference) -> Result<DocumentSnapshot, FirestoreError> {
-    b::get_doc(doc)
+/// Reads `doc`, returning `None` if it does not exist.
+pub async fn get_doc(doc: DocumentReference) -> Result<Option<DocumentSnapshot>, FirestoreError> {
+    let timer = metrics::start(OpKind::Get, || Some(doc.path()));
+
+    let res = b::get_doc(doc).await;
+
+    if let Some(timer) = timer {
+        timer.finish(res.is_ok() as usize);
+    }
+
+    res.map_err(|err| err.unchecked_into::<FirebaseError>().into())
+        .map(|snapshot| {
+            let snapshot = snapshot.unchecked_into::<DocumentSnapshot>();
+
+            snapshot.exists().then_some(snapshot)
+        })
+}
+
+/// Same as [`get_doc`], but fails with [`FirestoreErrorKind::NotFound`] if
+/// the document does not exist.
+pub async fn get_doc_required(doc: DocumentReference) -> Result<DocumentSnapshot, FirestoreError> {
+    let path = doc.path();
+
+    get_doc(doc).await?.ok_or_else(|| {
+        FirestoreError::new("not-found", &format!("document `{path}` does not exist"))
+    })
+}
+
+pub async fn get_docs(query: Query) -> Result<QuerySnapshot, FirestoreError> {
+    let timer = metrics::start(OpKind::GetDocs, || metrics::path_of(&query));
+
+    let res = b::get_docs(query)
         .await
         .map_err(|err| err.unchecked_into::<FirebaseError>().into())
-        .map(|snapshot| snapshot.unchecked_into())
+        .map(|snapshot| snapshot.unchecked_into::<QuerySnapshot>());
+
+    if let Some(timer) = timer {
+        timer.finish(res.as_ref().map_or(0, QuerySnapshot::size));
+    }
+
+    res
 }
 
-pub async fn get_docs(query: Query) -> Result<QuerySnapshot, FirestoreError> {
-    b::get_docs(query)
+pub async fn get_docs_from_cache(query: Query) -> Result<QuerySnapshot, FirestoreError> {
+    b::get_docs_from_cache(query)
         .await
         .map_err(|err| err.unchecked_into::<FirebaseError>().into())
         .map(|snapshot| snapshot.unchecked_into())
 }
 
+pub async fn set_doc<D: Into<JsValue>>(
+    doc: DocumentReference,
+    data: D,
+) -> Result<(), FirestoreError> {
+    let timer = metrics::start(OpKind::Set, || Some(doc.path()));
+
+    let res = b::set_doc(doc, data.into())
+        .await
+        .map_err(|err| err.unchecked_into::<FirebaseError>().into());
+
+    if let Some(timer) = timer {
+        timer.finish(res.is_ok() as usize);
+    }
+
+    res
+}
+
+/// Options for [`set_doc_with_options`], built like
+/// `SetDocOptions::new().merge(true)`.
+///
+/// The default overwrites the whole document, the same as [`set_doc`].
+#[derive(Clone, Debug, Default)]
+pub struct SetDocOptions {
+    pub merge: Option<bool>,
+    pub merge_fields: Option<Vec<FieldPath>>,
+}
+
+impl SetDocOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn merge(mut self, merge: bool) -> Self {
+        self.merge = Some(merge);
+
+        self
+    }
+
+    /// Only merges the given dot-separated `fields`, such as `address.city`.
+    pub fn merge_fields(self, fields: &[&str]) -> Self {
+        self.merge_field_paths(fields.iter().map(|&field| field.into()).collect())
+    }
+
+    pub fn merge_field_paths(mut self, fields: Vec<FieldPath>) -> Self {
+        self.merge_fields = Some(fields);
+
+        self
+    }
+
+    fn to_js(&self) -> JsValue {
+        let options = js_sys::Object::new();
+
+        if let Some(merge) = self.merge {
+            js_sys::Reflect::set(&options, &"merge".into(), &merge.into()).unwrap();
+        }
+
+        if let Some(merge_fields) = &self.merge_fields {
+            js_sys::Reflect::set(
+                &options,
+                &"mergeFields".into(),
+                &merge_fields.iter().collect::<js_sys::Array>(),
+            )
+            .unwrap();
+        }
+
+        options.into()
+    }
+}
+
 pub async fn set_doc_with_options<D: Into<JsValue>>(
     doc: DocumentReference,
     data: D,
     options: SetDocOptions,
 ) -> Result<(), FirestoreError> {
-    b::set_doc_with_options(doc, data.into(), options)
+    let timer = metrics::start(OpKind::Set, || Some(doc.path()));
+
+    let res = b::set_doc_with_options(doc, data.into(), options.to_js())
+        .await
+        .map_err(|err| err.unchecked_into::<FirebaseError>().into());
+
+    if let Some(timer) = timer {
+        timer.finish(res.is_ok() as usize);
+    }
+
+    res
+}
+
+/// Adds a new document with the given `data` and a randomly generated ID
+/// to `collection`.
+pub async fn add_doc<D: Into<JsValue>>(
+    collection: CollectionReference,
+    data: D,
+) -> Result<DocumentReference, FirestoreError> {
+    let timer = metrics::start(OpKind::Set, || Some(collection.path()));
+
+    let res = b::add_doc(collection, data.into())
         .await
         .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+        .map(|doc| doc.unchecked_into());
+
+    if let Some(timer) = timer {
+        timer.finish(res.is_ok() as usize);
+    }
+
+    res
+}
+
+pub async fn delete_doc(doc: DocumentReference) -> Result<(), FirestoreError> {
+    let timer = metrics::start(OpKind::Delete, || Some(doc.path()));
+
+    let res = b::delete_doc(doc)
+        .await
+        .map_err(|err| err.unchecked_into::<FirebaseError>().into());
+
+    if let Some(timer) = timer {
+        timer.finish(res.is_ok() as usize);
+    }
+
+    res
+}
+
+/// Updates fields of `doc` without overwriting the rest of the document.
+///
+/// Unlike [`set_doc_merge`], this fails with
+/// [`FirestoreErrorKind::NotFound`] if the document does not exist.
+pub async fn update_doc<D: Into<JsValue>>(
+    doc: DocumentReference,
+    data: D,
+) -> Result<(), FirestoreError> {
+    let timer = metrics::start(OpKind::Update, || Some(doc.path()));
+
+    let res = b::update_doc(doc, data.into())
+        .await
+        .map_err(|err| err.unchecked_into::<FirebaseError>().into());
+
+    if let Some(timer) = timer {
+        timer.finish(res.is_ok() as usize);
+    }
+
+    res
 }
 
+/// Writes `data` to `doc`, merging it into the existing document
+/// rather than overwriting it.
+pub async fn set_doc_merge<D: Into<JsValue>>(
+    doc: DocumentReference,
+    data: D,
+) -> Result<(), FirestoreError> {
+    set_doc_with_options(doc, data, SetDocOptions::default().merge(true)).await
+}
+
+/// Writes `data` to `doc`, only merging the given `fields` (which may be
+/// dot-separated paths) into the existing document.
+pub async fn set_doc_merge_fields<D: Into<JsValue>>(
+    doc: DocumentReference,
+    data: D,
+    fields: &[&str],
+) -> Result<(), FirestoreError> {
+    set_doc_with_options(doc, data, SetDocOptions::default().merge_fields(fields)).await
+}
+
+/// Gets a reference to the document at `path`, such as `users/alice`.
+///
+/// Fails with [`FirestoreErrorKind::InvalidArgument`] if `path` does not
+/// have an even number of segments, or contains an invalid segment.
+pub fn doc(firestore: Firestore, path: &str) -> Result<DocumentReference, FirestoreError> {
+    validate_path(path, true)?;
+
+    b::doc_js(firestore, path).map_err(Into::into)
+}
+
+/// Converts `ref_` into a field value, so that documents can reference
+/// each other.
+///
+/// Stored references are read back as [`DocumentReference`]s, which can
+/// be converted with [`TryFrom`].
+pub fn document_reference_value(ref_: DocumentReference) -> JsValue {
+    ref_.into()
+}
+
+impl TryFrom<JsValue> for DocumentReference {
+    type Error = JsValue;
+
+    /// Converts a reference field value, handing back anything else.
+    fn try_from(value: JsValue) -> Result<Self, Self::Error> {
+        value.dyn_into()
+    }
+}
+
+/// Gets a reference to the collection at `path`, such as
+/// `users/alice/posts`.
+///
+/// Fails with [`FirestoreErrorKind::InvalidArgument`] if `path` does not
+/// have an odd number of segments, or contains an invalid segment.
 pub fn collection(firestore: Firestore, path: &str) -> Result<CollectionReference, FirestoreError> {
-    b::collection(firestore, path).map_err(|err| err.into())
+    validate_path(path, false)?;
+
+    b::collection_js(firestore, path).map_err(Into::into)
+}
+
+/// Joins `segments` into a path for [`doc`] or [`collection`].
+///
+/// Firestore paths have no way of escaping `/`, so this fails with
+/// [`FirestoreErrorKind::InvalidArgument`] if any segment is not a valid
+/// ID, rather than silently producing a path to some other document.
+pub fn path_join(segments: &[&str]) -> Result<String, FirestoreError> {
+    for segment in segments {
+        validate_id(segment)?;
+    }
+
+    Ok(segments.join("/"))
+}
+
+fn validate_path(path: &str, is_doc: bool) -> Result<(), FirestoreError> {
+    match path_error(path, is_doc) {
+        Some(reason) => Err(FirestoreError::new(
+            "invalid-argument",
+            &format!("invalid path `{path}`: {reason}"),
+        )),
+        None => Ok(()),
+    }
+}
+
+fn path_error(path: &str, is_doc: bool) -> Option<String> {
+    // The SDK ignores leading and trailing slashes
+    let trimmed = path.trim_matches('/');
+
+    if trimmed.is_empty() {
+        return Some("must not be empty".to_owned());
+    }
+
+    let segments = trimmed.split('/').collect::<Vec<_>>();
+
+    for segment in &segments {
+        if let Some(reason) = id_error(segment) {
+            return Some(format!("segment `{segment}` {reason}"));
+        }
+    }
+
+    if is_doc && segments.len() % 2 != 0 {
+        Some(format!(
+            "document paths must have an even number of segments, got {}",
+            segments.len()
+        ))
+    } else if !is_doc && segments.len() % 2 == 0 {
+        Some(format!(
+            "collection paths must have an odd number of segments, got {}",
+            segments.len()
+        ))
+    } else {
+        None
+    }
+}
+
+/// Gets the sub-collection `collection_id` nested directly under `doc`.
+///
+/// Unlike [`collection`], this only accepts a single path segment, so it
+/// is not possible to accidentally nest the collection at the wrong level.
+pub fn sub_collection(
+    doc: DocumentReference,
+    collection_id: &str,
+) -> Result<CollectionReference, FirestoreError> {
+    b::sub_collection(doc, collection_id).map_err(|err| err.into())
+}
+
+/// Loads a Firestore bundle into the local cache.
+pub async fn load_bundle(
+    firestore: Firestore,
+    bundle_data: &[u8],
+) -> Result<LoadBundleTaskProgress, FirestoreError> {
+    let bundle_data = js_sys::Uint8Array::from(bundle_data).buffer();
+
+    load_bundle_js(firestore, bundle_data.into()).await
+}
+
+/// Same as [`load_bundle`], but for bundle data which already lives in JS.
+pub async fn load_bundle_from_array(
+    firestore: Firestore,
+    bundle_data: &js_sys::Uint8Array,
+) -> Result<LoadBundleTaskProgress, FirestoreError> {
+    // Copy into a fresh buffer, since `bundle_data` might only be a view
+    // into a larger `ArrayBuffer`
+    let bundle_data = js_sys::Uint8Array::new(bundle_data).buffer();
+
+    load_bundle_js(firestore, bundle_data.into()).await
+}
+
+/// Same as [`load_bundle`], but reads the bundle from a stream, such as
+/// the body of a `fetch` response.
+pub async fn load_bundle_from_stream(
+    firestore: Firestore,
+    bundle_data: &web_sys::ReadableStream,
+) -> Result<LoadBundleTaskProgress, FirestoreError> {
+    load_bundle_js(firestore, bundle_data.to_owned().into()).await
+}
+
+async fn load_bundle_js(
+    firestore: Firestore,
+    bundle_data: JsValue,
+) -> Result<LoadBundleTaskProgress, FirestoreError> {
+    let task = b::load_bundle(firestore, bundle_data);
+
+    // `LoadBundleTask` is a thenable, so we can adopt it as a promise
+    wasm_bindgen_futures::JsFuture::from(js_sys::Promise::resolve(&task))
+        .await
+        .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+        .map(|progress| progress.unchecked_into())
+}
+
+/// Gets a query which was saved in a previously loaded bundle,
+/// returning `None` if no query with the given `name` exists.
+pub async fn named_query(
+    firestore: Firestore,
+    name: &str,
+) -> Result<Option<Query>, FirestoreError> {
+    b::named_query(firestore, name)
+        .await
+        .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+        .map(|query| (!query.is_null()).then(|| query.unchecked_into()))
+}
+
+/// Shuts down `firestore`, after which it can no longer be used.
+pub async fn terminate(firestore: Firestore) -> Result<(), FirestoreError> {
+    b::terminate(firestore)
+        .await
+        .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+}
+
+/// Clears the offline cache.
+///
+/// This must be called either before `firestore` is used for the first
+/// time or after it has been [`terminate`]d, otherwise it fails with
+/// [`FirestoreErrorKind::FailedPrecondition`].
+pub async fn clear_persistence(firestore: Firestore) -> Result<(), FirestoreError> {
+    b::clear_indexed_db_persistence(firestore)
+        .await
+        .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+}
+
+pub fn enable_persistent_cache_index_auto_creation(
+    index_manager: &PersistentCacheIndexManager,
+) -> Result<(), FirestoreError> {
+    b::enable_persistent_cache_index_auto_creation(index_manager).map_err(Into::into)
+}
+
+pub fn disable_persistent_cache_index_auto_creation(
+    index_manager: &PersistentCacheIndexManager,
+) -> Result<(), FirestoreError> {
+    b::disable_persistent_cache_index_auto_creation(index_manager).map_err(Into::into)
+}
+
+pub fn delete_all_persistent_cache_indexes(
+    index_manager: &PersistentCacheIndexManager,
+) -> Result<(), FirestoreError> {
+    b::delete_all_persistent_cache_indexes(index_manager).map_err(Into::into)
+}
+
+impl CollectionReference {
+    /// Gets a reference to the document with the given `id` in this
+    /// collection.
+    ///
+    /// Fails with [`FirestoreErrorKind::InvalidArgument`] if `id` is not a
+    /// valid document ID.
+    pub fn doc_with_id(&self, id: &str) -> Result<DocumentReference, FirestoreError> {
+        validate_id(id)?;
+
+        b::doc_in_collection(self, id).map_err(Into::into)
+    }
+}
+
+fn validate_id(id: &str) -> Result<(), FirestoreError> {
+    match id_error(id) {
+        Some(reason) => Err(FirestoreError::new(
+            "invalid-argument",
+            &format!("invalid ID `{id}`: {reason}"),
+        )),
+        None => Ok(()),
+    }
+}
+
+fn id_error(id: &str) -> Option<&'static str> {
+    if id.is_empty() {
+        Some("must not be empty")
+    } else if id.contains('/') {
+        Some("must not contain `/`")
+    } else if id == "." || id == ".." {
+        Some("must not be `.` or `..`")
+    } else if id.starts_with("__") && id.ends_with("__") {
+        Some("must not match `__.*__`, which is reserved")
+    } else {
+        None
+    }
 }
 
 impl Transaction {
@@ -196,6 +793,24 @@ impl Transaction {
         self.update_js(doc, data).map_err(Into::into)
     }
 
+    /// Same as [`set`](Self::set), but serializes `data` with serde.
+    pub fn set_typed<D: Serialize + ?Sized>(
+        &self,
+        doc: DocumentReference,
+        data: &D,
+    ) -> Result<Self, FirestoreError> {
+        self.set(doc, to_value(data)?)
+    }
+
+    /// Same as [`update`](Self::update), but serializes `data` with serde.
+    pub fn update_typed<D: Serialize + ?Sized>(
+        &self,
+        doc: DocumentReference,
+        data: &D,
+    ) -> Result<Self, FirestoreError> {
+        self.update(doc, to_value(data)?)
+    }
+
     pub fn delete(&self, doc: DocumentReference) -> Result<Self, FirestoreError> {
         self.delete_js(doc).map_err(Into::into)
     }
@@ -209,6 +824,18 @@ pub enum TransactionError<Err> {
         #[source]
         FirestoreError,
     ),
+    /// Firestore gave up after retrying the transaction too many times,
+    /// such as because of contention with other writes, and this was the
+    /// last error.
+    ///
+    /// This is only reported when every attempt was used up and the last
+    /// error is one which the SDK retries. Other errors are reported as
+    /// [`Firestore`](Self::Firestore), even if they came from a retry.
+    ///
+    /// The SDK doesn't expose any ID for transactions, so there is nothing
+    /// to correlate with server logs beyond the error itself.
+    #[error("transaction failed after too many attempts: {0}")]
+    MaxAttemptsExceeded(#[source] FirestoreError),
     #[error("user-thrown error: {0:#?}")]
     User(#[source] Err),
 }
@@ -217,6 +844,7 @@ impl<Err: Clone> Clone for TransactionError<Err> {
     fn clone(&self) -> Self {
         match self {
             Self::Firestore(arg0) => Self::Firestore(arg0.clone()),
+            Self::MaxAttemptsExceeded(arg0) => Self::MaxAttemptsExceeded(arg0.clone()),
             Self::User(arg0) => Self::User(arg0.clone()),
         }
     }
@@ -227,15 +855,20 @@ impl<Err> TransactionError<Err> {
         matches!(self, Self::Firestore(_))
     }
 
+    pub fn is_max_attempts_exceeded(&self) -> bool {
+        matches!(self, Self::MaxAttemptsExceeded(_))
+    }
+
     pub fn is_user_err(&self) -> bool {
         matches!(self, Self::User(_))
     }
 
+    /// The error from Firestore, including when it gave up after too many
+    /// attempts.
     pub fn firestore_err(self) -> Option<FirestoreError> {
-        if let Self::Firestore(err) = self {
-            Some(err)
-        } else {
-            None
+        match self {
+            Self::Firestore(err) | Self::MaxAttemptsExceeded(err) => Some(err),
+            Self::User(_) => None,
         }
     }
 
@@ -251,6 +884,39 @@ impl<Err> TransactionError<Err> {
 #[wasm_bindgen]
 pub struct UserAbortedTransaction;
 
+/// How many times the SDK attempts a transaction by default.
+const MAX_TRANSACTION_ATTEMPTS: u32 = 5;
+
+/// Whether the SDK retries a transaction which failed with `kind`, the
+/// same as its `isRetryableTransactionError`.
+fn is_retried_by_transactions(kind: &FirestoreErrorKind) -> bool {
+    use FirestoreErrorKind::*;
+
+    matches!(
+        kind,
+        Aborted
+            | FailedPrecondition
+            | AlreadyExists
+            | Cancelled
+            | Unknown
+            | DeadlineExceeded
+            | ResourceExhausted
+            | Internal
+            | Unavailable
+            | Unauthenticated
+    )
+}
+
+/// Runs `update_fn` in a transaction.
+///
+/// Firestore calls `update_fn` again if the documents it read were
+/// changed before the transaction could be committed, so it may run
+/// several times and must not have side effects besides the writes on
+/// the [`Transaction`]. Only the result of the last attempt is returned.
+///
+/// `update_fn` has to be `'static`, since it is handed to JS. See
+/// [`run_transaction_with_context`] and [`run_transaction_with_state`]
+/// for ways to pass data in without wrapping it in `Rc<RefCell<_>>`.
 pub async fn run_transaction<F, Fut, T, Err>(
     firestore: Firestore,
     update_fn: F,
@@ -261,11 +927,16 @@ where
     T: 'static,
     Err: 'static,
 {
+    let timer = metrics::start(OpKind::Transaction, || None);
+
     let result: Rc<RefCell<Option<Result<T, Err>>>> = Default::default();
+    let attempts: Rc<Cell<u32>> = Default::default();
 
     let update_fn = Rc::new(RefCell::new(update_fn));
 
-    let update_fn = Closure::new(clone!([result], move |t| {
+    let update_fn = Closure::new(clone!([result, attempts], move |t| {
+        attempts.set(attempts.get() + 1);
+
         wasm_bindgen_futures::future_to_promise(clone!([update_fn, result], async move {
             let mut update_fn_borrow = update_fn.borrow_mut();
             let mut result_borrow = result.borrow_mut();
@@ -285,13 +956,28 @@ where
         }))
     }));
 
+    let res = b::run_transaction(firestore, &update_fn).await;
+
+    if let Some(timer) = timer {
+        timer.finish(0);
+    }
+
     // Check to see if the error is a firebase error
-    if let Err(err) = b::run_transaction(firestore, &update_fn).await {
+    if let Err(err) = res {
         if let Ok(err) = err.dyn_into::<js_sys::Object>() {
             if err.constructor().name() == "FirebaseError" {
-                return Err(TransactionError::Firestore(
-                    err.unchecked_into::<FirebaseError>().into(),
-                ));
+                let err = FirestoreError::from(err.unchecked_into::<FirebaseError>());
+
+                // Retried errors only surface once every attempt has been
+                // used up
+                let exhausted = attempts.get() >= MAX_TRANSACTION_ATTEMPTS
+                    && is_retried_by_transactions(&err.kind);
+
+                return Err(if exhausted {
+                    TransactionError::MaxAttemptsExceeded(err)
+                } else {
+                    TransactionError::Firestore(err)
+                });
             }
         }
     }
@@ -304,6 +990,224 @@ where
         .map_err(|err| TransactionError::User(err))
 }
 
+/// Same as [`run_transaction`], but moves `state` into every attempt
+/// and hands it back out together with the result.
+///
+/// Each attempt is given the `state` which the previous attempt returned.
+pub async fn run_transaction_with_state<S, F, Fut, T, Err>(
+    firestore: Firestore,
+    state: S,
+    mut update_fn: F,
+) -> (S, Result<T, TransactionError<Err>>)
+where
+    S: 'static,
+    F: FnMut(Transaction, S) -> Fut + 'static,
+    Fut: Future<Output = (S, Result<T, Err>)>,
+    T: 'static,
+    Err: 'static,
+{
+    let state = Rc::new(RefCell::new(Some(state)));
+
+    let result = run_transaction(
+        firestore,
+        clone!([state], move |transaction| {
+            // Attempts never overlap, so the previous one has always handed
+            // the state back by now
+            let attempt = update_fn(
+                transaction,
+                state.borrow_mut().take().expect("state to be available"),
+            );
+
+            clone!([state], async move {
+                let (attempt_state, result) = attempt.await;
+
+                *state.borrow_mut() = Some(attempt_state);
+
+                result
+            })
+        }),
+    )
+    .await;
+
+    let state = state.borrow_mut().take().expect("state to be handed back");
+
+    (state, result)
+}
+
+/// Same as [`run_transaction`], but shares `context` with every attempt,
+/// so that `update_fn` doesn't need to capture it.
+pub async fn run_transaction_with_context<Ctx, F, Fut, T, Err>(
+    firestore: Firestore,
+    context: Ctx,
+    mut update_fn: F,
+) -> Result<T, TransactionError<Err>>
+where
+    Ctx: 'static,
+    F: FnMut(Transaction, Rc<Ctx>) -> Fut + 'static,
+    Fut: Future<Output = Result<T, Err>>,
+    T: 'static,
+    Err: 'static,
+{
+    let context = Rc::new(context);
+
+    run_transaction(firestore, move |transaction| {
+        update_fn(transaction, context.clone())
+    })
+    .await
+}
+
+/// Creates a vector field value, such as an embedding, which can be
+/// written to a document.
+pub fn vector(values: &[f64]) -> VectorValue {
+    // The SDK expects a plain JS array, not a `Float64Array`
+    b::vector_js(values.iter().copied().map(JsValue::from).collect())
+}
+
+impl VectorValue {
+    pub fn to_array(&self) -> Vec<f64> {
+        self.to_array_js()
+            .iter()
+            .map(|value| value.as_f64().expect("vector values to be numbers"))
+            .collect()
+    }
+}
+
+impl Bytes {
+    /// Creates a bytes field value, such as for thumbnails or encrypted
+    /// data, which can be written to a document.
+    pub fn from_slice(data: &[u8]) -> Self {
+        Self::from_uint8_array(&js_sys::Uint8Array::from(data))
+    }
+
+    /// Copies the bytes out of JS.
+    ///
+    /// There is no way to borrow them as a `&[u8]`, as they live in JS
+    /// memory rather than in wasm memory.
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.to_uint8_array().to_vec()
+    }
+}
+
+impl From<&[u8]> for Bytes {
+    fn from(data: &[u8]) -> Self {
+        Self::from_slice(data)
+    }
+}
+
+/// Fetches all of `refs` in a single transaction, returning `None` for
+/// documents which do not exist.
+///
+/// A transaction can read at most 500 documents, so larger batches must
+/// be split up by the caller.
+pub async fn get_docs_batch(
+    refs: Vec<DocumentReference>,
+) -> Result<Vec<Option<DocumentSnapshot>>, FirestoreError> {
+    let Some(firestore) = refs.first().map(DocumentReference::firestore) else {
+        return Ok(Vec::new());
+    };
+
+    run_transaction(firestore, move |transaction| {
+        let refs = refs.clone();
+
+        async move {
+            let snapshots =
+                futures::future::try_join_all(refs.into_iter().map(|doc| transaction.get(doc)))
+                    .await?;
+
+            Ok::<_, FirestoreError>(
+                snapshots
+                    .into_iter()
+                    .map(|snapshot| snapshot.exists().then_some(snapshot))
+                    .collect(),
+            )
+        }
+    })
+    .await
+    .map_err(|err| match err {
+        TransactionError::Firestore(err)
+        | TransactionError::MaxAttemptsExceeded(err)
+        | TransactionError::User(err) => err,
+    })
+}
+
+/// Fetches all of `docs` concurrently, with at most `max_concurrent`
+/// requests in flight at once, returning `None` for documents which do
+/// not exist.
+///
+/// The results are in the same order as `docs`. Unlike
+/// [`get_docs_batch`], the documents are not read in a transaction, so
+/// they are not guaranteed to be consistent with each other.
+///
+/// # Panics
+///
+/// Panics if `max_concurrent` is zero.
+pub async fn get_all(
+    docs: Vec<DocumentReference>,
+    max_concurrent: usize,
+) -> Result<Vec<Option<DocumentSnapshot>>, FirestoreError> {
+    assert!(max_concurrent > 0, "max_concurrent must not be zero");
+
+    futures::stream::iter(docs)
+        .map(get_doc)
+        .buffered(max_concurrent)
+        .try_collect()
+        .await
+}
+
+/// Same as [`get_all`], but deserializes the documents.
+pub async fn get_all_typed<T: DeserializeOwned>(
+    docs: Vec<DocumentReference>,
+    max_concurrent: usize,
+) -> Result<Vec<Option<T>>, DataError> {
+    get_all(docs, max_concurrent)
+        .await?
+        .into_iter()
+        .map(|snapshot| match snapshot {
+            Some(snapshot) => snapshot.data_typed(),
+            None => Ok(None),
+        })
+        .collect()
+}
+
+/// The most values Firestore accepts in a single `in`, `not-in` or
+/// `array-contains-any` filter.
+const MAX_DISJUNCTION_VALUES: usize = 30;
+
+/// Runs an `in` query on `field` for any number of `values`, splitting it
+/// up into as many queries as needed to stay within Firestore's limit of
+/// 30 values per filter.
+///
+/// `extra` constraints are added to every query. The queries run
+/// concurrently, and documents matched by more than one of them are only
+/// returned once. The results are in no particular order.
+///
+/// Use [`document_id_path`] as the `field` to fetch documents by ID.
+pub async fn get_docs_where_in<F: Into<FieldPath>, V: Into<JsValue> + Clone>(
+    collection: CollectionReference,
+    field: F,
+    values: Vec<V>,
+    extra: Vec<QueryConstraint>,
+) -> Result<Vec<DocumentSnapshot>, FirestoreError> {
+    let field = field.into();
+
+    let queries = values.chunks(MAX_DISJUNCTION_VALUES).map(|chunk| {
+        let mut constraints = extra.clone();
+        constraints.push(where_in(field.clone(), chunk));
+
+        get_docs(query(collection.clone(), constraints))
+    });
+
+    let snapshots = futures::future::try_join_all(queries).await?;
+
+    let mut seen = HashSet::new();
+
+    Ok(snapshots
+        .iter()
+        .flat_map(QuerySnapshot::docs)
+        .filter(|doc| seen.insert(doc.ref_().path()))
+        .collect())
+}
+
 impl From<SystemTime> for Timestamp {
     fn from(time: SystemTime) -> Self {
         Self::from_millis(
@@ -319,3 +1223,196 @@ impl Default for Timestamp {
         Timestamp::now()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_code_strips_service_prefix() {
+        assert_eq!(
+            FirestoreErrorKind::from_code("firestore/permission-denied"),
+            FirestoreErrorKind::PermissionDenied,
+        );
+    }
+
+    #[test]
+    fn from_code_accepts_unprefixed_code() {
+        assert_eq!(
+            FirestoreErrorKind::from_code("permission-denied"),
+            FirestoreErrorKind::PermissionDenied,
+        );
+    }
+
+    #[test]
+    fn from_code_keeps_unrecognized_code_without_prefix() {
+        assert_eq!(
+            FirestoreErrorKind::from_code("firestore/bogus"),
+            FirestoreErrorKind::Unrecognized("bogus".to_owned()),
+        );
+    }
+
+    #[test]
+    fn transactions_retry_contention_but_not_permanent_errors() {
+        assert!(is_retried_by_transactions(&FirestoreErrorKind::Aborted));
+        assert!(is_retried_by_transactions(
+            &FirestoreErrorKind::FailedPrecondition
+        ));
+        assert!(is_retried_by_transactions(&FirestoreErrorKind::Unavailable));
+
+        assert!(!is_retried_by_transactions(
+            &FirestoreErrorKind::PermissionDenied
+        ));
+        assert!(!is_retried_by_transactions(&FirestoreErrorKind::NotFound));
+    }
+
+    #[test]
+    fn id_error_rejects_reserved_and_malformed_ids() {
+        assert_eq!(id_error("alice"), None);
+        assert_eq!(id_error("__alice"), None);
+        assert_eq!(id_error("..."), None);
+
+        assert!(id_error("").is_some());
+        assert!(id_error("a/b").is_some());
+        assert!(id_error(".").is_some());
+        assert!(id_error("..").is_some());
+        assert!(id_error("__alice__").is_some());
+        assert!(id_error("____").is_some());
+    }
+
+    #[test]
+    fn path_error_checks_segment_parity() {
+        assert_eq!(path_error("users/alice", true), None);
+        assert_eq!(path_error("users/alice/posts/1", true), None);
+        assert_eq!(path_error("users", false), None);
+        assert_eq!(path_error("users/alice/posts", false), None);
+
+        assert!(path_error("users", true).is_some());
+        assert!(path_error("users/alice/posts", true).is_some());
+        assert!(path_error("users/alice", false).is_some());
+    }
+
+    #[test]
+    fn path_error_ignores_leading_and_trailing_slashes() {
+        assert_eq!(path_error("/users/alice/", true), None);
+        assert_eq!(path_error("/users/", false), None);
+
+        assert!(path_error("/", false).is_some());
+        assert!(path_error("", true).is_some());
+    }
+
+    #[test]
+    fn path_error_rejects_invalid_segments() {
+        assert!(path_error("users//alice", true).is_some());
+        assert!(path_error("users/./posts", false).is_some());
+        assert!(path_error("users/../posts", false).is_some());
+        assert!(path_error("users/__alice__", true).is_some());
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod wasm_tests {
+    use super::*;
+    use crate::test_utils::{self, bundle, bundled_document, documents_root};
+    use futures::FutureExt;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    async fn load_bundle_round_trips_named_query() {
+        let firestore = test_utils::firestore();
+
+        let parent = documents_root();
+
+        let mut elements = vec![format!(
+            r#"{{"namedQuery":{{"name":"cities","bundledQuery":{{"parent":"{parent}","structuredQuery":{{"from":[{{"collectionId":"cities"}}]}}}},"readTime":{{"seconds":1,"nanos":0}}}}}}"#
+        )];
+        elements.extend(bundled_document(
+            "cities/sf",
+            r#"{"name":{"stringValue":"San Francisco"}}"#,
+        ));
+
+        let progress = load_bundle(firestore.clone(), &bundle(1, &elements))
+            .await
+            .unwrap();
+
+        assert_eq!(progress.task_state(), "Success");
+        assert_eq!(progress.documents_loaded(), 1);
+
+        let query = named_query(firestore.clone(), "cities")
+            .await
+            .unwrap()
+            .expect("query to be in the bundle");
+
+        let ids = get_docs_from_cache(query)
+            .await
+            .unwrap()
+            .docs()
+            .iter()
+            .map(DocumentSnapshot::id)
+            .collect::<Vec<_>>();
+
+        assert_eq!(ids, ["sf"]);
+
+        assert!(named_query(firestore, "missing").await.unwrap().is_none());
+    }
+
+    #[wasm_bindgen_test]
+    fn merge_fields_are_passed_as_field_paths() {
+        let options = SetDocOptions::new()
+            .merge_fields(&["address.city", "name"])
+            .to_js();
+
+        assert!(!js_sys::Reflect::has(&options, &"merge".into()).unwrap());
+
+        let fields = js_sys::Reflect::get(&options, &"mergeFields".into())
+            .unwrap()
+            .dyn_into::<js_sys::Array>()
+            .unwrap()
+            .iter()
+            .map(JsCast::unchecked_into::<FieldPath>)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            fields,
+            [field_path(&["address", "city"]), field_path(&["name"])]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn merge_fields_only_replace_the_listed_fields() {
+        let firestore = test_utils::firestore();
+
+        let elements = bundled_document(
+            "merge/doc",
+            r#"{"a":{"mapValue":{"fields":{"b":{"integerValue":"1"},"c":{"integerValue":"2"}}}},"d":{"integerValue":"3"}}"#,
+        );
+
+        load_bundle(firestore.clone(), &bundle(1, &elements))
+            .await
+            .unwrap();
+
+        let data = js_sys::JSON::parse(r#"{"a":{"b":10,"x":11},"d":12}"#).unwrap();
+
+        // Without a server the write never resolves, but it is applied to
+        // the cache as soon as it is made
+        let write = set_doc_with_options(
+            doc(firestore.clone(), "merge/doc").unwrap(),
+            data,
+            SetDocOptions::new().merge_fields(&["a.b"]),
+        );
+        assert!(write.now_or_never().is_none());
+
+        let snapshot = get_docs_from_cache(collection(firestore, "merge").unwrap().into())
+            .await
+            .unwrap()
+            .docs()
+            .remove(0);
+
+        let field = |path: &str| snapshot.get_field(&path.into()).as_f64();
+
+        assert_eq!(field("a.b"), Some(10.0));
+        assert_eq!(field("a.c"), Some(2.0));
+        assert_eq!(field("a.x"), None);
+        assert_eq!(field("d"), Some(3.0));
+    }
+}
@@ -0,0 +1,136 @@
+use super::{
+    bindings as b, get_docs, limit, sub_collection, CollectionReference, DocumentReference,
+    FirestoreError, WriteBatch,
+};
+use crate::FirebaseError;
+use futures::{future::LocalBoxFuture, FutureExt};
+use wasm_bindgen::{JsCast, JsValue};
+
+/// The most writes Firestore allows in a single batch.
+const MAX_BATCH_WRITES: u32 = 500;
+
+impl WriteBatch {
+    pub fn set(&self, doc: DocumentReference, data: JsValue) -> Result<Self, FirestoreError> {
+        self.set_js(doc, data).map_err(Into::into)
+    }
+
+    pub fn update(&self, doc: DocumentReference, data: JsValue) -> Result<Self, FirestoreError> {
+        self.update_js(doc, data).map_err(Into::into)
+    }
+
+    pub fn delete(&self, doc: DocumentReference) -> Result<Self, FirestoreError> {
+        self.delete_js(doc).map_err(Into::into)
+    }
+
+    /// Commits all of the writes in the batch atomically.
+    ///
+    /// The SDK doesn't report when the server applied the writes. To find
+    /// out, also write [`server_timestamp`](super::server_timestamp) to a
+    /// field in the batch, and read it back once the commit is done.
+    pub async fn commit(&self) -> Result<(), FirestoreError> {
+        self.commit_js()
+            .await
+            .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+    }
+}
+
+/// Deletes every document in `collection`, `batch_size` documents at a
+/// time, returning how many documents were deleted.
+///
+/// Subcollections are **not** deleted, since the client SDK cannot list
+/// them. See [`delete_collection_recursive`] for deleting subcollections
+/// with known names.
+///
+/// Fails with [`FirestoreErrorKind::InvalidArgument`](super::FirestoreErrorKind::InvalidArgument)
+/// unless `batch_size` is between 1 and 500.
+pub async fn delete_collection(
+    collection: CollectionReference,
+    batch_size: u32,
+) -> Result<u64, FirestoreError> {
+    delete_collection_with_progress(collection, batch_size, |_| {}).await
+}
+
+/// Same as [`delete_collection`], but calls `on_progress` with the total
+/// number of documents deleted so far after every batch.
+pub async fn delete_collection_with_progress(
+    collection: CollectionReference,
+    batch_size: u32,
+    on_progress: impl FnMut(u64),
+) -> Result<u64, FirestoreError> {
+    delete_collection_inner(collection, batch_size, &[], on_progress).await
+}
+
+/// Same as [`delete_collection_with_progress`], but first deletes the
+/// `subcollections` with the given names under each document.
+///
+/// Only subcollections directly under the documents of `collection` are
+/// deleted, and they are not counted in the returned total.
+pub async fn delete_collection_recursive(
+    collection: CollectionReference,
+    batch_size: u32,
+    subcollections: &[&str],
+    on_progress: impl FnMut(u64),
+) -> Result<u64, FirestoreError> {
+    delete_collection_inner(collection, batch_size, subcollections, on_progress).await
+}
+
+async fn delete_collection_inner(
+    collection: CollectionReference,
+    batch_size: u32,
+    subcollections: &[&str],
+    mut on_progress: impl FnMut(u64),
+) -> Result<u64, FirestoreError> {
+    if !(1..=MAX_BATCH_WRITES).contains(&batch_size) {
+        return Err(FirestoreError::new(
+            "invalid-argument",
+            &format!("batch size must be between 1 and {MAX_BATCH_WRITES}, got {batch_size}"),
+        ));
+    }
+
+    let firestore = collection.firestore();
+
+    let mut deleted = 0;
+
+    loop {
+        let page = get_docs(b::query_with(
+            collection.clone().into(),
+            vec![limit(batch_size as i32)],
+        ))
+        .await?
+        .docs();
+
+        if page.is_empty() {
+            break;
+        }
+
+        let batch = b::write_batch(firestore.clone());
+
+        for doc in &page {
+            for &name in subcollections {
+                delete_subcollection(sub_collection(doc.ref_(), name)?, batch_size).await?;
+            }
+
+            batch.delete(doc.ref_())?;
+        }
+
+        batch.commit().await?;
+
+        deleted += page.len() as u64;
+
+        on_progress(deleted);
+
+        if page.len() < batch_size as usize {
+            break;
+        }
+    }
+
+    Ok(deleted)
+}
+
+// Not an `async fn`, since async functions can't recurse directly
+fn delete_subcollection(
+    collection: CollectionReference,
+    batch_size: u32,
+) -> LocalBoxFuture<'static, Result<u64, FirestoreError>> {
+    async move { delete_collection_inner(collection, batch_size, &[], |_| {}).await }.boxed_local()
+}
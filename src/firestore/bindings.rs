@@ -1,19 +1,15 @@
-use crate::FirebaseError;
+use crate::{app::FirebaseApp, FirebaseError};
 use js_sys::Date;
+use std::hash::{Hash, Hasher};
 use wasm_bindgen::prelude::*;
 
-#[derive(Clone, Copy, Debug, Default)]
-#[wasm_bindgen(getter_with_clone)]
-pub struct SetDocOptions {
-    pub merge: Option<bool>,
-}
-
 #[wasm_bindgen(module = "firebase/firestore")]
 extern "C" {
     #[derive(Clone, Debug)]
     pub type Firestore;
     #[derive(Clone, Debug)]
     pub type DocumentReference;
+    #[wasm_bindgen(extends = Query)]
     #[derive(Clone, Debug)]
     pub type CollectionReference;
     #[derive(Clone, Debug)]
@@ -23,19 +19,51 @@ extern "C" {
     #[derive(Clone, Debug)]
     pub type QuerySnapshot;
     #[derive(Clone, Debug)]
+    pub type SnapshotMetadata;
+    #[derive(Clone, Debug)]
+    pub type DocumentChange;
+    #[derive(Clone, Debug)]
     pub type QueryConstraint;
     #[derive(Clone, Debug)]
     pub type Transaction;
     #[derive(Clone, Debug)]
+    pub type WriteBatch;
+    #[derive(Clone, Debug)]
     pub type Timestamp;
     #[derive(Clone, Debug)]
     pub type FieldValue;
+    #[derive(Clone, Debug)]
+    pub type GeoPoint;
+    #[derive(Clone, Debug)]
+    pub type FieldPath;
+    #[derive(Clone, Debug)]
+    pub type VectorValue;
+    #[derive(Clone, Debug)]
+    pub type Bytes;
+    #[derive(Clone, Debug)]
+    pub type PersistentCacheIndexManager;
+    #[derive(Clone, Debug)]
+    pub type LoadBundleTask;
+    #[derive(Clone, Debug)]
+    pub type LoadBundleTaskProgress;
 
     #[wasm_bindgen(js_name = getFirestore)]
-    pub fn get_firestore() -> Firestore;
+    pub fn get_firestore(app: &FirebaseApp) -> Firestore;
 
-    #[wasm_bindgen(catch)]
-    pub fn doc(firestore: Firestore, path: &str) -> Result<DocumentReference, JsValue>;
+    #[wasm_bindgen(js_name = getFirestore)]
+    pub fn get_firestore_default() -> Firestore;
+
+    #[wasm_bindgen(js_name = doc, catch)]
+    pub(crate) fn doc_js(
+        firestore: Firestore,
+        path: &str,
+    ) -> Result<DocumentReference, FirebaseError>;
+
+    /// Creates a reference to a new document in `collection` with a
+    /// randomly generated ID. Nothing is written until the reference is
+    /// used in a write.
+    #[wasm_bindgen(js_name = doc)]
+    pub fn doc_auto_id(collection: CollectionReference) -> DocumentReference;
 
     #[wasm_bindgen(js_name = getDoc, catch)]
     pub async fn get_doc(doc: DocumentReference) -> Result<JsValue, JsValue>;
@@ -43,6 +71,14 @@ extern "C" {
     #[wasm_bindgen(js_name = getDocs, catch)]
     pub async fn get_docs(query: Query) -> Result<JsValue, JsValue>;
 
+    /// Same as [`get_docs`], but starts the request right away rather than
+    /// when first polled.
+    #[wasm_bindgen(js_name = getDocs)]
+    pub(crate) fn get_docs_promise(query: Query) -> js_sys::Promise;
+
+    #[wasm_bindgen(js_name = getDocsFromCache, catch)]
+    pub async fn get_docs_from_cache(query: Query) -> Result<JsValue, JsValue>;
+
     #[wasm_bindgen(js_name = "setDoc", catch)]
     pub async fn set_doc(doc: DocumentReference, data: JsValue) -> Result<(), JsValue>;
 
@@ -50,21 +86,38 @@ extern "C" {
     pub async fn set_doc_with_options(
         doc: DocumentReference,
         data: JsValue,
-        options: SetDocOptions,
+        options: JsValue,
     ) -> Result<(), JsValue>;
 
     #[wasm_bindgen(js_name = "updateDoc", catch)]
     pub async fn update_doc(doc: DocumentReference, data: JsValue) -> Result<(), JsValue>;
 
+    #[wasm_bindgen(js_name = "updateDoc", catch, variadic)]
+    pub(crate) async fn update_doc_fields(
+        doc: DocumentReference,
+        field: JsValue,
+        value: JsValue,
+        more_fields_and_values: Vec<JsValue>,
+    ) -> Result<(), JsValue>;
+
     #[wasm_bindgen(js_name = "addDoc", catch)]
-    pub async fn add_doc(collection: CollectionReference, data: JsValue) -> Result<(), JsValue>;
+    pub async fn add_doc(
+        collection: CollectionReference,
+        data: JsValue,
+    ) -> Result<JsValue, JsValue>;
 
-    #[wasm_bindgen(catch)]
-    pub fn collection(
+    #[wasm_bindgen(js_name = collection, catch)]
+    pub(crate) fn collection_js(
         firestore: Firestore,
         path: &str,
     ) -> Result<CollectionReference, FirebaseError>;
 
+    #[wasm_bindgen(js_name = collection, catch)]
+    pub fn sub_collection(
+        doc: DocumentReference,
+        collection_id: &str,
+    ) -> Result<CollectionReference, FirebaseError>;
+
     #[wasm_bindgen(js_name = onSnapshot)]
     pub fn on_snapshot_doc(
         reference: DocumentReference,
@@ -77,14 +130,32 @@ extern "C" {
         observer: &Closure<dyn FnMut(QuerySnapshot)>,
     ) -> js_sys::Function;
 
+    #[wasm_bindgen(js_name = onSnapshot)]
+    pub(crate) fn on_snapshot_with_options(
+        reference: &JsValue,
+        options: &JsValue,
+        on_next: &Closure<dyn FnMut(JsValue)>,
+        on_error: &Closure<dyn FnMut(JsValue)>,
+    ) -> js_sys::Function;
+
     #[wasm_bindgen(variadic)]
     pub fn query(collection: CollectionReference, constraints: Vec<QueryConstraint>) -> Query;
 
+    /// Same as [`query`], but adds the constraints to an existing query.
+    #[wasm_bindgen(js_name = query, variadic)]
+    pub(crate) fn query_with(query: Query, constraints: Vec<QueryConstraint>) -> Query;
+
     #[wasm_bindgen(js_name = "where")]
-    pub fn where_(field_path: &str, op_str: &str, value: JsValue) -> QueryConstraint;
+    pub fn where_(field_path: &FieldPath, op_str: &str, value: JsValue) -> QueryConstraint;
+
+    #[wasm_bindgen(js_name = "or", variadic)]
+    pub fn or(constraints: Vec<QueryConstraint>) -> QueryConstraint;
+
+    #[wasm_bindgen(js_name = "and", variadic)]
+    pub fn and(constraints: Vec<QueryConstraint>) -> QueryConstraint;
 
     #[wasm_bindgen(js_name = "orderBy")]
-    pub fn order_by(field_path: &str, direction_str: &str) -> QueryConstraint;
+    pub fn order_by(field_path: &FieldPath, direction_str: &str) -> QueryConstraint;
 
     #[wasm_bindgen]
     pub fn limit(number: i32) -> QueryConstraint;
@@ -92,9 +163,18 @@ extern "C" {
     #[wasm_bindgen(js_name = "startAfter")]
     pub fn start_after(doc: DocumentReference) -> QueryConstraint;
 
+    #[wasm_bindgen(js_name = "startAfter")]
+    pub fn start_after_snapshot(snapshot: &DocumentSnapshot) -> QueryConstraint;
+
+    #[wasm_bindgen(js_name = "startAfter", variadic)]
+    pub(crate) fn start_after_values(values: Vec<JsValue>) -> QueryConstraint;
+
     #[wasm_bindgen(js_name = deleteDoc, catch)]
     pub async fn delete_doc(doc: DocumentReference) -> Result<(), JsValue>;
 
+    #[wasm_bindgen(js_name = writeBatch)]
+    pub fn write_batch(firestore: Firestore) -> WriteBatch;
+
     #[wasm_bindgen(js_name = runTransaction, catch)]
     pub async fn run_transaction(
         firestore: Firestore,
@@ -104,6 +184,54 @@ extern "C" {
     #[wasm_bindgen(js_name = serverTimestamp)]
     pub fn server_timestamp() -> FieldValue;
 
+    #[wasm_bindgen(catch)]
+    pub async fn terminate(firestore: Firestore) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(js_name = clearIndexedDbPersistence, catch)]
+    pub async fn clear_indexed_db_persistence(firestore: Firestore) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(js_name = getPersistentCacheIndexManager)]
+    pub fn get_persistent_cache_index_manager(
+        firestore: Firestore,
+    ) -> Option<PersistentCacheIndexManager>;
+
+    #[wasm_bindgen(js_name = enablePersistentCacheIndexAutoCreation, catch)]
+    pub fn enable_persistent_cache_index_auto_creation(
+        index_manager: &PersistentCacheIndexManager,
+    ) -> Result<(), FirebaseError>;
+
+    #[wasm_bindgen(js_name = disablePersistentCacheIndexAutoCreation, catch)]
+    pub fn disable_persistent_cache_index_auto_creation(
+        index_manager: &PersistentCacheIndexManager,
+    ) -> Result<(), FirebaseError>;
+
+    #[wasm_bindgen(js_name = deleteAllPersistentCacheIndexes, catch)]
+    pub fn delete_all_persistent_cache_indexes(
+        index_manager: &PersistentCacheIndexManager,
+    ) -> Result<(), FirebaseError>;
+
+    #[wasm_bindgen(js_name = documentId)]
+    pub fn document_id() -> FieldPath;
+
+    #[wasm_bindgen(js_name = vector)]
+    pub(crate) fn vector_js(values: js_sys::Array) -> VectorValue;
+
+    #[wasm_bindgen(js_name = loadBundle)]
+    pub fn load_bundle(firestore: Firestore, bundle_data: JsValue) -> LoadBundleTask;
+
+    #[wasm_bindgen(js_name = namedQuery, catch)]
+    pub async fn named_query(firestore: Firestore, name: &str) -> Result<JsValue, JsValue>;
+
+    // =========================================================================
+    //                            Firestore
+    // =========================================================================
+
+    #[wasm_bindgen(method, getter)]
+    pub fn app(this: &Firestore) -> FirebaseApp;
+
+    #[wasm_bindgen(method, getter, js_name = type)]
+    pub fn type_(this: &Firestore) -> String;
+
     // =========================================================================
     //                            QuerySnapshot
     // =========================================================================
@@ -117,6 +245,35 @@ extern "C" {
     #[wasm_bindgen(method, getter)]
     pub fn docs(this: &QuerySnapshot) -> Vec<DocumentSnapshot>;
 
+    #[wasm_bindgen(method, getter)]
+    pub fn metadata(this: &QuerySnapshot) -> SnapshotMetadata;
+
+    /// The changes since the previous snapshot of the same listener, or
+    /// every document as added for the first snapshot.
+    #[wasm_bindgen(method, js_name = docChanges)]
+    pub fn doc_changes(this: &QuerySnapshot) -> Vec<DocumentChange>;
+
+    // =========================================================================
+    //                            DocumentChange
+    // =========================================================================
+
+    /// One of `added`, `modified` or `removed`.
+    #[wasm_bindgen(method, getter, js_name = type)]
+    pub fn type_(this: &DocumentChange) -> String;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn doc(this: &DocumentChange) -> DocumentSnapshot;
+
+    /// The index of the document before this change, or `-1` if it was
+    /// added.
+    #[wasm_bindgen(method, getter, js_name = oldIndex)]
+    pub fn old_index(this: &DocumentChange) -> i32;
+
+    /// The index of the document after this change, or `-1` if it was
+    /// removed.
+    #[wasm_bindgen(method, getter, js_name = newIndex)]
+    pub fn new_index(this: &DocumentChange) -> i32;
+
     // =========================================================================
     //                            DocumentSnapshot
     // =========================================================================
@@ -130,9 +287,34 @@ extern "C" {
     #[wasm_bindgen(method, getter)]
     pub fn id(this: &DocumentSnapshot) -> String;
 
-    #[wasm_bindgen(method, js_name = "ref")]
+    #[wasm_bindgen(method, getter, js_name = "ref")]
     pub fn ref_(this: &DocumentSnapshot) -> DocumentReference;
 
+    /// Gets the field at `field_path`, or `undefined` if the field or the
+    /// document does not exist.
+    #[wasm_bindgen(method, js_name = get)]
+    pub fn get_field(this: &DocumentSnapshot, field_path: &FieldPath) -> JsValue;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn metadata(this: &DocumentSnapshot) -> SnapshotMetadata;
+
+    // =========================================================================
+    //                            SnapshotMetadata
+    // =========================================================================
+
+    /// Whether the snapshot came from the local cache rather than the
+    /// server.
+    #[wasm_bindgen(method, getter, js_name = fromCache)]
+    pub fn from_cache(this: &SnapshotMetadata) -> bool;
+
+    /// Whether the snapshot contains local writes which have not been
+    /// committed to the server yet.
+    #[wasm_bindgen(method, getter, js_name = hasPendingWrites)]
+    pub fn has_pending_writes(this: &SnapshotMetadata) -> bool;
+
+    #[wasm_bindgen(method, js_name = isEqual)]
+    pub fn is_equal(this: &SnapshotMetadata, other: &SnapshotMetadata) -> bool;
+
     // =========================================================================
     //                            DocumentSnapshot
     // =========================================================================
@@ -163,10 +345,40 @@ extern "C" {
         doc: DocumentReference,
     ) -> Result<Transaction, FirebaseError>;
 
+    // =========================================================================
+    //                            WriteBatch
+    // =========================================================================
+
+    #[wasm_bindgen(method, js_name = set, catch)]
+    pub(crate) fn set_js(
+        this: &WriteBatch,
+        doc: DocumentReference,
+        data: JsValue,
+    ) -> Result<WriteBatch, FirebaseError>;
+
+    #[wasm_bindgen(method, js_name = update, catch)]
+    pub(crate) fn update_js(
+        this: &WriteBatch,
+        doc: DocumentReference,
+        data: JsValue,
+    ) -> Result<WriteBatch, FirebaseError>;
+
+    #[wasm_bindgen(method, js_name = delete, catch)]
+    pub(crate) fn delete_js(
+        this: &WriteBatch,
+        doc: DocumentReference,
+    ) -> Result<WriteBatch, FirebaseError>;
+
+    #[wasm_bindgen(method, js_name = commit, catch)]
+    pub(crate) async fn commit_js(this: &WriteBatch) -> Result<(), JsValue>;
+
     // =========================================================================
     //                            Timestamp
     // =========================================================================
 
+    #[wasm_bindgen(constructor)]
+    pub fn new(seconds: f64, nanoseconds: u32) -> Timestamp;
+
     #[wasm_bindgen(js_namespace = Timestamp)]
     pub fn now() -> Timestamp;
 
@@ -176,6 +388,12 @@ extern "C" {
     #[wasm_bindgen(js_namespace = Timestamp, js_name = fromMillis)]
     pub fn from_millis(milliseconds: f64) -> Timestamp;
 
+    #[wasm_bindgen(method, getter)]
+    pub fn seconds(this: &Timestamp) -> f64;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn nanoseconds(this: &Timestamp) -> u32;
+
     #[wasm_bindgen(method, js_name = toDate)]
     pub fn to_date(this: &Timestamp) -> Date;
 
@@ -195,15 +413,149 @@ extern "C" {
     #[wasm_bindgen(method, getter)]
     pub fn id(this: &DocumentReference) -> String;
 
+    #[wasm_bindgen(method, getter)]
+    pub fn parent(this: &DocumentReference) -> CollectionReference;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn firestore(this: &DocumentReference) -> Firestore;
+
     #[wasm_bindgen(js_name = refEqual)]
     pub(crate) fn ref_equal(left: &DocumentReference, right: &DocumentReference) -> bool;
 
+    // =========================================================================
+    //                            CollectionReference
+    // =========================================================================
+
+    #[wasm_bindgen(method, getter)]
+    pub fn path(this: &CollectionReference) -> String;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn id(this: &CollectionReference) -> String;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn parent(this: &CollectionReference) -> Option<DocumentReference>;
+
+    #[wasm_bindgen(js_name = doc, catch)]
+    pub(crate) fn doc_in_collection(
+        collection: &CollectionReference,
+        path: &str,
+    ) -> Result<DocumentReference, FirebaseError>;
+
+    #[wasm_bindgen(js_name = refEqual)]
+    pub(crate) fn collection_ref_equal(
+        left: &CollectionReference,
+        right: &CollectionReference,
+    ) -> bool;
+
     // =========================================================================
     //                            Query
     // =========================================================================
 
+    #[wasm_bindgen(method, getter)]
+    pub fn firestore(this: &Query) -> Firestore;
+
     #[wasm_bindgen(js_name = queryEqual)]
     pub(crate) fn query_equal(left: &Query, right: &Query) -> bool;
+
+    // =========================================================================
+    //                            Snapshots
+    // =========================================================================
+
+    #[wasm_bindgen(js_name = snapshotEqual)]
+    pub(crate) fn document_snapshot_equal(
+        left: &DocumentSnapshot,
+        right: &DocumentSnapshot,
+    ) -> bool;
+
+    #[wasm_bindgen(js_name = snapshotEqual)]
+    pub(crate) fn query_snapshot_equal(left: &QuerySnapshot, right: &QuerySnapshot) -> bool;
+
+    // =========================================================================
+    //                            GeoPoint
+    // =========================================================================
+
+    #[wasm_bindgen(constructor)]
+    pub fn new(latitude: f64, longitude: f64) -> GeoPoint;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn latitude(this: &GeoPoint) -> f64;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn longitude(this: &GeoPoint) -> f64;
+
+    #[wasm_bindgen(method, js_name = isEqual)]
+    pub fn is_equal(this: &GeoPoint, other: &GeoPoint) -> bool;
+
+    // =========================================================================
+    //                            FieldPath
+    // =========================================================================
+
+    #[wasm_bindgen(constructor, variadic)]
+    pub(crate) fn new_js(field_names: Vec<JsValue>) -> FieldPath;
+
+    #[wasm_bindgen(method, js_name = isEqual)]
+    pub fn is_equal(this: &FieldPath, other: &FieldPath) -> bool;
+
+    // =========================================================================
+    //                            VectorValue
+    // =========================================================================
+
+    #[wasm_bindgen(method, js_name = toArray)]
+    pub(crate) fn to_array_js(this: &VectorValue) -> js_sys::Array;
+
+    #[wasm_bindgen(method, js_name = isEqual)]
+    pub fn is_equal(this: &VectorValue, other: &VectorValue) -> bool;
+
+    // =========================================================================
+    //                            Bytes
+    // =========================================================================
+
+    #[wasm_bindgen(static_method_of = Bytes, js_name = fromUint8Array)]
+    pub(crate) fn from_uint8_array(array: &js_sys::Uint8Array) -> Bytes;
+
+    /// Fails if `base64` is not valid base64.
+    #[wasm_bindgen(static_method_of = Bytes, js_name = fromBase64String, catch)]
+    pub fn from_base64_string(base64: &str) -> Result<Bytes, FirebaseError>;
+
+    #[wasm_bindgen(method, js_name = toUint8Array)]
+    pub(crate) fn to_uint8_array(this: &Bytes) -> js_sys::Uint8Array;
+
+    #[wasm_bindgen(method, js_name = toBase64)]
+    pub fn to_base64(this: &Bytes) -> String;
+
+    #[wasm_bindgen(method, js_name = isEqual)]
+    pub fn is_equal(this: &Bytes, other: &Bytes) -> bool;
+
+    // =========================================================================
+    //                            LoadBundleTask
+    // =========================================================================
+
+    #[wasm_bindgen(method, js_name = onProgress)]
+    pub fn on_progress(
+        this: &LoadBundleTask,
+        next: &Closure<dyn FnMut(LoadBundleTaskProgress)>,
+        error: Option<&Closure<dyn FnMut(JsValue)>>,
+        complete: Option<&Closure<dyn FnMut()>>,
+    );
+
+    // =========================================================================
+    //                            LoadBundleTaskProgress
+    // =========================================================================
+
+    #[wasm_bindgen(method, getter, js_name = documentsLoaded)]
+    pub fn documents_loaded(this: &LoadBundleTaskProgress) -> usize;
+
+    #[wasm_bindgen(method, getter, js_name = totalDocuments)]
+    pub fn total_documents(this: &LoadBundleTaskProgress) -> usize;
+
+    #[wasm_bindgen(method, getter, js_name = bytesLoaded)]
+    pub fn bytes_loaded(this: &LoadBundleTaskProgress) -> usize;
+
+    #[wasm_bindgen(method, getter, js_name = totalBytes)]
+    pub fn total_bytes(this: &LoadBundleTaskProgress) -> usize;
+
+    #[wasm_bindgen(method, getter, js_name = taskState)]
+    pub fn task_state(this: &LoadBundleTaskProgress) -> String;
 }
 
 impl PartialEq for Timestamp {
@@ -213,6 +565,39 @@ impl PartialEq for Timestamp {
 }
 impl Eq for Timestamp {}
 
+impl PartialEq for GeoPoint {
+    fn eq(&self, other: &Self) -> bool {
+        self.is_equal(other)
+    }
+}
+
+impl PartialEq for FieldPath {
+    fn eq(&self, other: &Self) -> bool {
+        self.is_equal(other)
+    }
+}
+impl Eq for FieldPath {}
+
+impl PartialEq for VectorValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.is_equal(other)
+    }
+}
+
+impl PartialEq for Bytes {
+    fn eq(&self, other: &Self) -> bool {
+        self.is_equal(other)
+    }
+}
+impl Eq for Bytes {}
+
+impl PartialEq for SnapshotMetadata {
+    fn eq(&self, other: &Self) -> bool {
+        self.is_equal(other)
+    }
+}
+impl Eq for SnapshotMetadata {}
+
 impl PartialEq for DocumentReference {
     fn eq(&self, other: &Self) -> bool {
         ref_equal(self, other)
@@ -220,9 +605,93 @@ impl PartialEq for DocumentReference {
 }
 impl Eq for DocumentReference {}
 
+// Refs that are `isEqual` always share a path, so hashing the path is
+// consistent with `PartialEq`.
+impl Hash for DocumentReference {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.path().hash(state);
+    }
+}
+
 impl PartialEq for Query {
     fn eq(&self, other: &Self) -> bool {
         query_equal(self, other)
     }
 }
 impl Eq for Query {}
+
+impl PartialEq for CollectionReference {
+    fn eq(&self, other: &Self) -> bool {
+        collection_ref_equal(self, other)
+    }
+}
+impl Eq for CollectionReference {}
+
+impl Hash for CollectionReference {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.path().hash(state);
+    }
+}
+
+impl PartialEq for DocumentSnapshot {
+    fn eq(&self, other: &Self) -> bool {
+        document_snapshot_equal(self, other)
+    }
+}
+impl Eq for DocumentSnapshot {}
+
+impl PartialEq for QuerySnapshot {
+    fn eq(&self, other: &Self) -> bool {
+        query_snapshot_equal(self, other)
+    }
+}
+impl Eq for QuerySnapshot {}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod wasm_tests {
+    use super::*;
+    use crate::{
+        firestore as fs,
+        test_utils::{self, bundle, bundled_document},
+    };
+    use std::collections::HashSet;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn references_to_the_same_path_are_equal() {
+        let firestore = test_utils::firestore();
+
+        let a = fs::doc(firestore.clone(), "a/b").unwrap();
+        let b = fs::doc(firestore.clone(), "a/b").unwrap();
+
+        assert_eq!(a, b);
+        assert_ne!(a, fs::doc(firestore.clone(), "a/c").unwrap());
+        assert_eq!(HashSet::from([a, b]).len(), 1);
+
+        let a = fs::collection(firestore.clone(), "a").unwrap();
+        let b = fs::collection(firestore, "a").unwrap();
+
+        assert_eq!(a, b);
+        assert_eq!(HashSet::from([a, b]).len(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    async fn snapshots_of_the_same_documents_are_equal() {
+        let firestore = test_utils::firestore();
+
+        let elements = bundled_document("equality/a", r#"{"n":{"integerValue":"1"}}"#);
+
+        fs::load_bundle(firestore.clone(), &bundle(1, &elements))
+            .await
+            .unwrap();
+
+        let query = || Query::from(fs::collection(firestore.clone(), "equality").unwrap());
+
+        let a = fs::get_docs_from_cache(query()).await.unwrap();
+        let b = fs::get_docs_from_cache(query()).await.unwrap();
+
+        assert_eq!(a, b);
+        assert_eq!(a.size(), 1);
+        assert_eq!(a.docs(), b.docs());
+    }
+}
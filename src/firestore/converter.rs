@@ -0,0 +1,161 @@
+use super::{
+    add_doc, data::to_value, get_doc, get_docs, set_doc, CollectionReference, DataError,
+    DocumentReference, DocumentSnapshot, FirestoreError, Query,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::marker::PhantomData;
+use wasm_bindgen::JsValue;
+
+/// Converts between `T` and the data stored in Firestore, so that reads
+/// and writes through the references returned by `with_converter` speak
+/// `T` directly.
+pub trait DataConverter<T> {
+    fn to_firestore(&self, value: &T) -> Result<JsValue, FirestoreError>;
+
+    /// Converts an existing document into `T`.
+    fn from_firestore(&self, snapshot: &DocumentSnapshot) -> Result<T, DataError>;
+}
+
+/// A [`DataConverter`] for any type implementing [`Serialize`] and
+/// [`Deserialize`](serde::Deserialize).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SerdeConverter;
+
+impl<T: Serialize + DeserializeOwned> DataConverter<T> for SerdeConverter {
+    fn to_firestore(&self, value: &T) -> Result<JsValue, FirestoreError> {
+        to_value(value)
+    }
+
+    fn from_firestore(&self, snapshot: &DocumentSnapshot) -> Result<T, DataError> {
+        serde_wasm_bindgen::from_value(snapshot.data()).map_err(DataError::Deserialize)
+    }
+}
+
+impl DocumentReference {
+    pub fn with_converter<T, C: DataConverter<T>>(
+        self,
+        converter: C,
+    ) -> ConvertedDocumentReference<T, C> {
+        ConvertedDocumentReference {
+            reference: self,
+            converter,
+            _data: PhantomData,
+        }
+    }
+}
+
+impl CollectionReference {
+    pub fn with_converter<T, C: DataConverter<T>>(
+        self,
+        converter: C,
+    ) -> ConvertedCollectionReference<T, C> {
+        ConvertedCollectionReference {
+            reference: self,
+            converter,
+            _data: PhantomData,
+        }
+    }
+}
+
+impl Query {
+    pub fn with_converter<T, C: DataConverter<T>>(self, converter: C) -> ConvertedQuery<T, C> {
+        ConvertedQuery {
+            query: self,
+            converter,
+            _data: PhantomData,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ConvertedDocumentReference<T, C> {
+    reference: DocumentReference,
+    converter: C,
+    _data: PhantomData<T>,
+}
+
+impl<T, C: DataConverter<T>> ConvertedDocumentReference<T, C> {
+    pub fn reference(&self) -> &DocumentReference {
+        &self.reference
+    }
+
+    /// Reads the document, returning `None` if it does not exist.
+    pub async fn get_doc(&self) -> Result<Option<T>, DataError> {
+        match get_doc(self.reference.clone()).await? {
+            Some(snapshot) => self.converter.from_firestore(&snapshot).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn set_doc(&self, value: &T) -> Result<(), FirestoreError> {
+        let data = self.converter.to_firestore(value)?;
+
+        set_doc(self.reference.clone(), data).await
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ConvertedCollectionReference<T, C> {
+    reference: CollectionReference,
+    converter: C,
+    _data: PhantomData<T>,
+}
+
+impl<T, C: DataConverter<T> + Clone> ConvertedCollectionReference<T, C> {
+    pub fn reference(&self) -> &CollectionReference {
+        &self.reference
+    }
+
+    pub fn doc_with_id(
+        &self,
+        id: &str,
+    ) -> Result<ConvertedDocumentReference<T, C>, FirestoreError> {
+        self.reference
+            .doc_with_id(id)
+            .map(|doc| doc.with_converter(self.converter.clone()))
+    }
+
+    pub async fn add_doc(
+        &self,
+        value: &T,
+    ) -> Result<ConvertedDocumentReference<T, C>, FirestoreError> {
+        let data = self.converter.to_firestore(value)?;
+
+        add_doc(self.reference.clone(), data)
+            .await
+            .map(|doc| doc.with_converter(self.converter.clone()))
+    }
+
+    pub async fn get_docs(&self) -> Result<Vec<T>, DataError> {
+        convert_docs(self.reference.clone().into(), &self.converter).await
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ConvertedQuery<T, C> {
+    query: Query,
+    converter: C,
+    _data: PhantomData<T>,
+}
+
+impl<T, C: DataConverter<T>> ConvertedQuery<T, C> {
+    pub fn query(&self) -> &Query {
+        &self.query
+    }
+
+    pub async fn get_docs(&self) -> Result<Vec<T>, DataError> {
+        convert_docs(self.query.clone(), &self.converter).await
+    }
+}
+
+async fn convert_docs<T, C: DataConverter<T>>(
+    query: Query,
+    converter: &C,
+) -> Result<Vec<T>, DataError> {
+    get_docs(query)
+        .await?
+        .docs()
+        .iter()
+        .map(|doc| converter.from_firestore(doc))
+        .collect()
+}
@@ -0,0 +1,304 @@
+use super::{
+    bindings as b, doc, DocumentReference, DocumentSnapshot, FieldPath, Firestore, FirestoreError,
+    GeoPoint, QueryConstraint, Timestamp,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{JsCast, JsValue};
+
+/// A pagination cursor which, unlike a [`DocumentSnapshot`], can be
+/// stored in a URL with [`to_token`](Self::to_token).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Cursor {
+    values: Vec<CursorValue>,
+    /// Only set when the query is also ordered by document ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    document_id: Option<String>,
+}
+
+/// The field values which can be stored in a [`Cursor`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "t", content = "v")]
+enum CursorValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Timestamp {
+        seconds: f64,
+        nanoseconds: u32,
+    },
+    GeoPoint {
+        latitude: f64,
+        longitude: f64,
+    },
+    /// The path of a [`DocumentReference`].
+    Reference(String),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CursorError {
+    #[error("field `{0}` has a value which cannot be stored in a cursor")]
+    UnsupportedValue(String),
+    #[error("invalid cursor token")]
+    InvalidToken,
+}
+
+impl Cursor {
+    /// Creates a cursor pointing at `snapshot` for a query ordered by the
+    /// `order_by` fields, in the same order as the query's `order_by`
+    /// constraints.
+    ///
+    /// Only `null`, booleans, numbers, strings, [`Timestamp`]s,
+    /// [`GeoPoint`]s and [`DocumentReference`]s are supported.
+    pub fn from_snapshot(
+        snapshot: &DocumentSnapshot,
+        order_by: &[&str],
+    ) -> Result<Self, CursorError> {
+        Ok(Self {
+            values: Self::values(snapshot, order_by)?,
+            document_id: None,
+        })
+    }
+
+    /// Same as [`from_snapshot`](Self::from_snapshot), but also stores the
+    /// ID of `snapshot`, to break ties between documents with the same
+    /// field values.
+    ///
+    /// The query must then be ordered by
+    /// [`document_id_path`](super::document_id_path) after the `order_by`
+    /// fields, or Firestore rejects the cursor for having too many values.
+    pub fn from_snapshot_with_document_id(
+        snapshot: &DocumentSnapshot,
+        order_by: &[&str],
+    ) -> Result<Self, CursorError> {
+        Ok(Self {
+            values: Self::values(snapshot, order_by)?,
+            document_id: Some(snapshot.id()),
+        })
+    }
+
+    fn values(
+        snapshot: &DocumentSnapshot,
+        order_by: &[&str],
+    ) -> Result<Vec<CursorValue>, CursorError> {
+        order_by
+            .iter()
+            .map(|&field| {
+                CursorValue::from_js(&snapshot.get_field(&FieldPath::from(field)))
+                    .ok_or_else(|| CursorError::UnsupportedValue(field.to_owned()))
+            })
+            .collect()
+    }
+
+    /// Encodes the cursor as a URL-safe string.
+    pub fn to_token(&self) -> String {
+        let value = serde_wasm_bindgen::to_value(self).expect("cursor to be serializable");
+
+        let json = js_sys::JSON::stringify(&value)
+            .expect("cursor to be serializable")
+            .as_string()
+            .unwrap();
+
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    /// Decodes a token created by [`to_token`](Self::to_token).
+    pub fn from_token(token: &str) -> Result<Self, CursorError> {
+        let json = URL_SAFE_NO_PAD
+            .decode(token)
+            .ok()
+            .and_then(|json| String::from_utf8(json).ok())
+            .ok_or(CursorError::InvalidToken)?;
+
+        let value = js_sys::JSON::parse(&json).map_err(|_| CursorError::InvalidToken)?;
+
+        serde_wasm_bindgen::from_value(value).map_err(|_| CursorError::InvalidToken)
+    }
+}
+
+impl CursorValue {
+    fn from_js(value: &JsValue) -> Option<Self> {
+        if value.is_null() {
+            Some(Self::Null)
+        } else if let Some(value) = value.as_bool() {
+            Some(Self::Bool(value))
+        } else if let Some(number) = value.as_f64() {
+            Some(Self::Number(number))
+        } else if let Some(value) = value.as_string() {
+            Some(Self::String(value))
+        } else if let Some(timestamp) = value.dyn_ref::<Timestamp>() {
+            Some(Self::Timestamp {
+                seconds: timestamp.seconds(),
+                nanoseconds: timestamp.nanoseconds(),
+            })
+        } else if let Some(geo_point) = value.dyn_ref::<GeoPoint>() {
+            Some(Self::GeoPoint {
+                latitude: geo_point.latitude(),
+                longitude: geo_point.longitude(),
+            })
+        } else {
+            value
+                .dyn_ref::<DocumentReference>()
+                .map(|doc| Self::Reference(doc.path()))
+        }
+    }
+
+    fn to_js(&self, firestore: &Firestore) -> Result<JsValue, FirestoreError> {
+        Ok(match self {
+            Self::Null => JsValue::NULL,
+            Self::Bool(value) => (*value).into(),
+            Self::Number(number) => (*number).into(),
+            Self::String(value) => value.into(),
+            Self::Timestamp {
+                seconds,
+                nanoseconds,
+            } => Timestamp::new(*seconds, *nanoseconds).into(),
+            Self::GeoPoint {
+                latitude,
+                longitude,
+            } => GeoPoint::new(*latitude, *longitude).into(),
+            Self::Reference(path) => doc(firestore.clone(), path)?.into(),
+        })
+    }
+}
+
+/// Starts the results of a query after `cursor`.
+///
+/// The query must be ordered by the same fields the cursor was created
+/// with, followed by the document ID for cursors created with
+/// [`Cursor::from_snapshot_with_document_id`].
+pub fn start_after_cursor(
+    firestore: &Firestore,
+    cursor: &Cursor,
+) -> Result<QueryConstraint, FirestoreError> {
+    let mut values = cursor
+        .values
+        .iter()
+        .map(|value| value.to_js(firestore))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    values.extend(cursor.document_id.as_deref().map(JsValue::from));
+
+    Ok(b::start_after_values(values))
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod wasm_tests {
+    use super::*;
+    use crate::{
+        firestore::{
+            collection, document_id_path, get_docs_from_cache, load_bundle, order_by,
+            order_by_field_path, query, Direction,
+        },
+        test_utils::{self, bundle, bundled_document},
+    };
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn cursor(document_id: Option<&str>) -> Cursor {
+        Cursor {
+            values: vec![
+                CursorValue::Null,
+                CursorValue::Bool(true),
+                CursorValue::Number(1.5),
+                CursorValue::String("a".to_owned()),
+                CursorValue::Timestamp {
+                    seconds: 1.0,
+                    nanoseconds: 2,
+                },
+                CursorValue::GeoPoint {
+                    latitude: 3.0,
+                    longitude: 4.0,
+                },
+                CursorValue::Reference("cities/sf".to_owned()),
+            ],
+            document_id: document_id.map(ToOwned::to_owned),
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn tokens_round_trip() {
+        for document_id in [None, Some("sf")] {
+            let cursor = cursor(document_id);
+
+            assert_eq!(Cursor::from_token(&cursor.to_token()).unwrap(), cursor);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn tampered_tokens_are_rejected() {
+        let token = cursor(Some("sf")).to_token();
+
+        let not_base64 = format!("{token}!");
+        let not_json = URL_SAFE_NO_PAD.encode(&URL_SAFE_NO_PAD.decode(&token).unwrap()[..10]);
+        let not_a_cursor = URL_SAFE_NO_PAD.encode(r#"{"values":[{"t":"Number","v":"1"}]}"#);
+
+        for token in [not_base64, not_json, not_a_cursor] {
+            assert!(matches!(
+                Cursor::from_token(&token),
+                Err(CursorError::InvalidToken)
+            ));
+        }
+    }
+
+    #[wasm_bindgen_test]
+    async fn cursors_only_include_the_document_id_when_asked_to() {
+        let firestore = test_utils::firestore();
+
+        let elements = [("a", 1), ("b", 2), ("c", 2)]
+            .into_iter()
+            .flat_map(|(id, n)| {
+                bundled_document(
+                    &format!("cursor/{id}"),
+                    &format!(r#"{{"n":{{"integerValue":"{n}"}}}}"#),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        load_bundle(firestore.clone(), &bundle(3, &elements))
+            .await
+            .unwrap();
+
+        let ids = |constraints| {
+            let query = query(
+                collection(firestore.clone(), "cursor").unwrap(),
+                constraints,
+            );
+
+            async move {
+                get_docs_from_cache(query)
+                    .await
+                    .unwrap()
+                    .docs()
+                    .iter()
+                    .map(DocumentSnapshot::id)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        let by_field = Cursor {
+            values: vec![CursorValue::Number(1.0)],
+            document_id: None,
+        };
+
+        let constraints = vec![
+            order_by("n", Direction::Asc),
+            start_after_cursor(&firestore, &by_field).unwrap(),
+        ];
+
+        assert_eq!(ids(constraints).await, ["b", "c"]);
+
+        let by_field_and_id = Cursor {
+            values: vec![CursorValue::Number(2.0)],
+            document_id: Some("b".to_owned()),
+        };
+
+        let constraints = vec![
+            order_by("n", Direction::Asc),
+            order_by_field_path(document_id_path(), Direction::Asc),
+            start_after_cursor(&firestore, &by_field_and_id).unwrap(),
+        ];
+
+        assert_eq!(ids(constraints).await, ["c"]);
+    }
+}
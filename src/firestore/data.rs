@@ -0,0 +1,443 @@
+use super::{
+    add_doc, doc_snapshots, get_doc, get_docs, query_snapshots, set_doc, set_doc_with_options,
+    where_, Bytes, CollectionReference, DocumentChange, DocumentReference, DocumentSnapshot,
+    FieldPath, FieldValue, FirestoreError, GeoPoint, Query, QueryConstraint, QueryConstraintOp,
+    QuerySnapshot, SetDocOptions, Timestamp, VectorValue,
+};
+use futures::{future, stream, Stream, StreamExt};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use wasm_bindgen::{JsCast, JsValue};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DataError {
+    #[error("firestore error: {0}")]
+    Firestore(
+        #[from]
+        #[source]
+        FirestoreError,
+    ),
+    #[error("failed to deserialize document: {0}")]
+    Deserialize(#[source] serde_wasm_bindgen::Error),
+    #[error("failed to deserialize document `{id}`: {source}")]
+    DeserializeDoc {
+        id: String,
+        #[source]
+        source: serde_wasm_bindgen::Error,
+    },
+}
+
+impl DocumentSnapshot {
+    /// Deserializes the document's data, returning `None` if the document
+    /// does not exist.
+    pub fn data_typed<T: DeserializeOwned>(&self) -> Result<Option<T>, DataError> {
+        if !self.exists() {
+            return Ok(None);
+        }
+
+        serde_wasm_bindgen::from_value(self.data())
+            .map(Some)
+            .map_err(DataError::Deserialize)
+    }
+}
+
+/// Listens to a single field of `doc`, only yielding when its value
+/// changes.
+///
+/// `None` is yielded, once, when the document is deleted or the field is
+/// removed.
+pub fn watch_field<T: DeserializeOwned + PartialEq + Clone>(
+    doc: DocumentReference,
+    field_path: &str,
+) -> impl Stream<Item = Result<Option<T>, DataError>> {
+    let field_path = FieldPath::from(field_path);
+
+    let mut last: Option<Option<T>> = None;
+
+    doc_snapshots(doc).filter_map(move |snapshot| {
+        let value = snapshot.map_err(DataError::from).and_then(|snapshot| {
+            let field = snapshot.get_field(&field_path);
+
+            if field.is_undefined() {
+                Ok(None)
+            } else {
+                serde_wasm_bindgen::from_value(field)
+                    .map(Some)
+                    .map_err(DataError::Deserialize)
+            }
+        });
+
+        let changed = match &value {
+            Ok(value) => last.as_ref() != Some(value),
+            Err(_) => true,
+        };
+
+        if let Ok(value) = &value {
+            last = Some(value.clone());
+        }
+
+        future::ready(changed.then_some(value))
+    })
+}
+
+pub async fn get_doc_typed<T: DeserializeOwned>(
+    doc: DocumentReference,
+) -> Result<Option<T>, DataError> {
+    match get_doc(doc).await? {
+        Some(snapshot) => snapshot.data_typed(),
+        None => Ok(None),
+    }
+}
+
+/// Runs `query` and deserializes every document, paired with its ID.
+///
+/// Fails with [`DataError::DeserializeDoc`] on the first document which
+/// cannot be deserialized. See [`get_docs_typed_lenient`] to get the
+/// rest of the documents anyway.
+pub async fn get_docs_typed<T: DeserializeOwned>(
+    query: Query,
+) -> Result<Vec<(String, T)>, DataError> {
+    get_docs(query)
+        .await?
+        .docs()
+        .into_iter()
+        .map(|doc| {
+            let id = doc.id();
+
+            match serde_wasm_bindgen::from_value(doc.data()) {
+                Ok(value) => Ok((id, value)),
+                Err(source) => Err(DataError::DeserializeDoc { id, source }),
+            }
+        })
+        .collect()
+}
+
+/// The documents read by [`get_docs_typed_lenient`].
+#[derive(Debug)]
+pub struct TypedDocs<T> {
+    /// Successfully deserialized documents, paired with their IDs.
+    pub docs: Vec<(String, T)>,
+    /// IDs of documents which could not be deserialized, and why.
+    pub failures: Vec<(String, serde_wasm_bindgen::Error)>,
+}
+
+/// Same as [`get_docs_typed`], but collects documents which cannot be
+/// deserialized instead of failing.
+pub async fn get_docs_typed_lenient<T: DeserializeOwned>(
+    query: Query,
+) -> Result<TypedDocs<T>, FirestoreError> {
+    Ok(typed_docs(get_docs(query).await?))
+}
+
+fn typed_docs<T: DeserializeOwned>(snapshot: QuerySnapshot) -> TypedDocs<T> {
+    let mut typed_docs = TypedDocs {
+        docs: Vec::new(),
+        failures: Vec::new(),
+    };
+
+    for doc in snapshot.docs() {
+        let id = doc.id();
+
+        match serde_wasm_bindgen::from_value(doc.data()) {
+            Ok(value) => typed_docs.docs.push((id, value)),
+            Err(err) => typed_docs.failures.push((id, err)),
+        }
+    }
+
+    typed_docs
+}
+
+/// Listens to the results of `query`, deserializing every document of
+/// each snapshot.
+///
+/// Documents which cannot be deserialized are reported in
+/// [`TypedDocs::failures`] without ending the stream.
+pub fn query_items<T: DeserializeOwned>(
+    query: Query,
+) -> impl Stream<Item = Result<TypedDocs<T>, FirestoreError>> {
+    query_snapshots(query).map(|snapshot| snapshot.map(typed_docs))
+}
+
+/// A change to the results of a query, as yielded by
+/// [`query_item_changes`].
+///
+/// Applying the changes in order to a `Vec` keeps it in the same order as
+/// the query results; removals refer to indices before the change, and
+/// additions to indices after it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ItemChange<T> {
+    Added {
+        id: String,
+        item: T,
+        index: usize,
+    },
+    Modified {
+        id: String,
+        item: T,
+        old_index: usize,
+        new_index: usize,
+    },
+    Removed {
+        id: String,
+        index: usize,
+    },
+}
+
+/// Listens to the results of `query`, yielding every added, modified and
+/// removed document rather than whole snapshots.
+///
+/// The first snapshot yields every document as added. A document which
+/// cannot be deserialized yields a [`DataError::DeserializeDoc`] without
+/// ending the stream, while a listener error is yielded as
+/// [`DataError::Firestore`] and ends it.
+pub fn query_item_changes<T: DeserializeOwned>(
+    query: Query,
+) -> impl Stream<Item = Result<ItemChange<T>, DataError>> {
+    query_snapshots(query).flat_map(|snapshot| {
+        let changes = match snapshot {
+            Ok(snapshot) => snapshot
+                .doc_changes()
+                .into_iter()
+                .map(item_change)
+                .collect(),
+            Err(err) => vec![Err(err.into())],
+        };
+
+        stream::iter(changes)
+    })
+}
+
+fn item_change<T: DeserializeOwned>(change: DocumentChange) -> Result<ItemChange<T>, DataError> {
+    let doc = change.doc();
+    let id = doc.id();
+
+    // Indices are only -1 for the side of the change which doesn't exist
+    let old_index = change.old_index().max(0) as usize;
+    let new_index = change.new_index().max(0) as usize;
+
+    if change.type_() == "removed" {
+        return Ok(ItemChange::Removed {
+            id,
+            index: old_index,
+        });
+    }
+
+    let item = match serde_wasm_bindgen::from_value(doc.data()) {
+        Ok(item) => item,
+        Err(source) => return Err(DataError::DeserializeDoc { id, source }),
+    };
+
+    Ok(if change.type_() == "added" {
+        ItemChange::Added {
+            id,
+            item,
+            index: new_index,
+        }
+    } else {
+        ItemChange::Modified {
+            id,
+            item,
+            old_index,
+            new_index,
+        }
+    })
+}
+
+/// Serializes `value` and writes it to `doc`.
+///
+/// Maps are written as objects, and `None` fields are omitted from the
+/// document. SDK types such as [`Timestamp`], [`GeoPoint`], [`Bytes`] and
+/// [`DocumentReference`] are written as-is.
+pub async fn set_doc_serde<T: Serialize + ?Sized>(
+    doc: DocumentReference,
+    value: &T,
+    options: Option<SetDocOptions>,
+) -> Result<(), FirestoreError> {
+    let data = to_value(value)?;
+
+    match options {
+        Some(options) => set_doc_with_options(doc, data, options).await,
+        None => set_doc(doc, data).await,
+    }
+}
+
+/// Serializes `data` and adds it to `collection` as a new document with
+/// a randomly generated ID.
+pub async fn add_doc_typed<D: Serialize + ?Sized>(
+    collection: CollectionReference,
+    data: &D,
+) -> Result<DocumentReference, FirestoreError> {
+    add_doc(collection, to_value(data)?).await
+}
+
+/// Same as [`where_`], but serializes `value` with serde.
+///
+/// Useful for values without an `Into<JsValue>` impl, such as a
+/// `Vec<&str>` for [`QueryConstraintOp::In`]. [`Timestamp`] and
+/// [`DocumentReference`] values are passed through as-is.
+pub fn where_serde<F: Into<FieldPath>, V: Serialize + ?Sized>(
+    field_path: F,
+    op: QueryConstraintOp,
+    value: &V,
+) -> Result<QueryConstraint, FirestoreError> {
+    Ok(where_(field_path, op, to_value(value)?))
+}
+
+/// Serializes `value` into a JS value suitable for writing to Firestore.
+pub(crate) fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<JsValue, FirestoreError> {
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+
+    let value = value.serialize(&serializer).map_err(|err| {
+        FirestoreError::new(
+            "serialization-error",
+            &format!("failed to serialize data: {err}"),
+        )
+    })?;
+
+    strip_undefined(&value);
+
+    Ok(value)
+}
+
+/// Removes `undefined` properties, which is what `serde_wasm_bindgen`
+/// serializes `None` as, so they are omitted instead of being rejected
+/// by the SDK.
+fn strip_undefined(value: &JsValue) {
+    if let Some(array) = value.dyn_ref::<js_sys::Array>() {
+        for item in array.iter() {
+            strip_undefined(&item);
+        }
+    } else if is_plain_object(value) {
+        let object = value.unchecked_ref::<js_sys::Object>();
+
+        for key in js_sys::Object::keys(object).iter() {
+            let property = js_sys::Reflect::get(object, &key).unwrap();
+
+            if property.is_undefined() {
+                js_sys::Reflect::delete_property(object, &key).unwrap();
+            } else {
+                strip_undefined(&property);
+            }
+        }
+    }
+}
+
+fn is_plain_object(value: &JsValue) -> bool {
+    value.is_object()
+        && JsValue::from(js_sys::Object::get_prototype_of(value))
+            == JsValue::from(js_sys::Object::get_prototype_of(&js_sys::Object::new()))
+}
+
+// These are passed through to the SDK as-is when (de)serialized with
+// `serde_wasm_bindgen`, rather than being turned into plain objects
+macro_rules! impl_preserve_serialize {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Serialize for $ty {
+                fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serde_wasm_bindgen::preserve::serialize(self, serializer)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_preserve_deserialize {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    serde_wasm_bindgen::preserve::deserialize(deserializer)
+                }
+            }
+        )*
+    };
+}
+
+impl_preserve_serialize!(
+    Bytes,
+    DocumentReference,
+    FieldValue,
+    GeoPoint,
+    Timestamp,
+    VectorValue
+);
+impl_preserve_deserialize!(Bytes, DocumentReference, GeoPoint, Timestamp, VectorValue);
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod wasm_tests {
+    use super::*;
+    use crate::{
+        firestore::{collection, get_docs_from_cache, load_bundle, query},
+        test_utils::{self, bundle, bundled_document},
+    };
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    async fn matching_ids(constraint: QueryConstraint) -> Vec<String> {
+        let firestore = test_utils::firestore();
+
+        let elements = [("a", 1), ("b", 3), ("c", 5)]
+            .into_iter()
+            .flat_map(|(name, seconds)| {
+                bundled_document(
+                    &format!("serde/{name}"),
+                    &format!(
+                        r#"{{"name":{{"stringValue":"{name}"}},"at":{{"timestampValue":"1970-01-01T00:00:0{seconds}Z"}}}}"#
+                    ),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        load_bundle(firestore.clone(), &bundle(3, &elements))
+            .await
+            .unwrap();
+
+        let collection = collection(firestore, "serde").unwrap();
+
+        get_docs_from_cache(query(collection, vec![constraint]))
+            .await
+            .unwrap()
+            .docs()
+            .iter()
+            .map(DocumentSnapshot::id)
+            .collect()
+    }
+
+    #[wasm_bindgen_test]
+    fn vecs_serialize_to_arrays() {
+        let value = to_value(&vec!["a", "b"]).unwrap();
+
+        assert!(js_sys::Array::is_array(&value));
+        assert_eq!(
+            value.unchecked_into::<js_sys::Array>().to_vec(),
+            [JsValue::from("a"), JsValue::from("b")],
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn timestamps_are_passed_through() {
+        let timestamp = Timestamp::new(2.0, 0);
+
+        let value = to_value(&timestamp).unwrap();
+
+        assert_eq!(value.dyn_into::<Timestamp>().unwrap(), timestamp);
+    }
+
+    #[wasm_bindgen_test]
+    async fn where_serde_in_matches_listed_strings() {
+        let constraint = where_serde("name", QueryConstraintOp::In, &vec!["a", "b"]).unwrap();
+
+        assert_eq!(matching_ids(constraint).await, ["a", "b"]);
+    }
+
+    #[wasm_bindgen_test]
+    async fn where_serde_compares_timestamps() {
+        let constraint = where_serde(
+            "at",
+            QueryConstraintOp::GreaterThan,
+            &Timestamp::new(2.0, 0),
+        )
+        .unwrap();
+
+        assert_eq!(matching_ids(constraint).await, ["b", "c"]);
+    }
+}
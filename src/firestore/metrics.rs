@@ -0,0 +1,125 @@
+use super::{CollectionReference, DocumentReference};
+use std::{cell::RefCell, rc::Rc, time::Duration};
+use wasm_bindgen::{JsCast, JsValue};
+
+/// The kind of operation an [`OpMetric`] was recorded for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OpKind {
+    Get,
+    GetDocs,
+    ListenerUpdate,
+    Set,
+    Update,
+    Delete,
+    Transaction,
+}
+
+/// A single Firestore operation, as reported to the sink installed with
+/// [`set_metrics_sink`].
+#[derive(Clone, Debug)]
+pub struct OpMetric {
+    pub kind: OpKind,
+    /// The path of the document or collection the operation was made on.
+    ///
+    /// This is `None` for transactions and for queries with constraints,
+    /// whose collection can't be read back from the SDK.
+    pub path: Option<String>,
+    /// The number of documents read or written.
+    ///
+    /// Always zero for [`OpKind::Transaction`], since the reads and writes
+    /// made through the [`Transaction`](super::Transaction) aren't counted.
+    pub documents: usize,
+    /// How long the operation took. Always zero for
+    /// [`OpKind::ListenerUpdate`].
+    pub elapsed: Duration,
+}
+
+type Sink = Rc<dyn Fn(OpMetric)>;
+
+thread_local! {
+    static SINK: RefCell<Option<Sink>> = RefCell::new(None);
+}
+
+/// Installs `sink` to be called after every read, write, listener update
+/// and transaction made through this crate's wrappers, replacing any
+/// previous sink.
+///
+/// Until a sink is installed, no metrics are collected at all.
+pub fn set_metrics_sink(sink: impl Fn(OpMetric) + 'static) {
+    SINK.with(|s| *s.borrow_mut() = Some(Rc::new(sink)));
+}
+
+/// Removes the sink installed with [`set_metrics_sink`].
+pub fn clear_metrics_sink() {
+    SINK.with(|s| *s.borrow_mut() = None);
+}
+
+fn sink() -> Option<Sink> {
+    SINK.with(|s| s.borrow().clone())
+}
+
+/// Measures an operation from when it's created until [`OpTimer::finish`].
+pub(crate) struct OpTimer {
+    kind: OpKind,
+    path: Option<String>,
+    started: f64,
+}
+
+/// Starts timing an operation, or returns `None` if no sink is installed.
+pub(crate) fn start(kind: OpKind, path: impl FnOnce() -> Option<String>) -> Option<OpTimer> {
+    sink()?;
+
+    Some(OpTimer {
+        kind,
+        path: path(),
+        started: js_sys::Date::now(),
+    })
+}
+
+impl OpTimer {
+    pub(crate) fn finish(self, documents: usize) {
+        let elapsed = (js_sys::Date::now() - self.started).max(0.0);
+
+        report(OpMetric {
+            kind: self.kind,
+            path: self.path,
+            documents,
+            elapsed: Duration::from_secs_f64(elapsed / 1000.0),
+        });
+    }
+}
+
+/// Reports an operation which isn't timed, such as a listener update.
+///
+/// `details` gives the path and number of documents, and is only called
+/// if a sink is installed.
+pub(crate) fn record(kind: OpKind, details: impl FnOnce() -> (Option<String>, usize)) {
+    if sink().is_some() {
+        let (path, documents) = details();
+
+        report(OpMetric {
+            kind,
+            path,
+            documents,
+            elapsed: Duration::ZERO,
+        });
+    }
+}
+
+fn report(metric: OpMetric) {
+    // The sink is called outside of the borrow so it may replace itself
+    if let Some(sink) = sink() {
+        sink(metric);
+    }
+}
+
+/// The path of a document, collection or query reference, if it has one.
+pub(crate) fn path_of(reference: &JsValue) -> Option<String> {
+    if let Some(doc) = reference.dyn_ref::<DocumentReference>() {
+        Some(doc.path())
+    } else {
+        reference
+            .dyn_ref::<CollectionReference>()
+            .map(CollectionReference::path)
+    }
+}
@@ -0,0 +1,107 @@
+use super::{
+    bindings as b, limit, start_after_snapshot, DocumentSnapshot, FirestoreError, Query,
+    QuerySnapshot,
+};
+use crate::FirebaseError;
+use futures::{FutureExt, Stream, StreamExt};
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+
+/// Pages through the results of a query using cursors.
+///
+/// The first page is requested as soon as the `PagedQuery` is created.
+/// Each following page is requested once the previous one has been
+/// consumed, starting after its last document. Paging ends after a page
+/// which comes back short, and empty pages are never yielded, so an empty
+/// collection only costs a single query. It also ends after the first
+/// error.
+pub struct PagedQuery {
+    query: Query,
+    page_size: u32,
+    last_doc: Option<DocumentSnapshot>,
+    done: bool,
+    pending: Option<JsFuture>,
+}
+
+impl PagedQuery {
+    /// # Panics
+    ///
+    /// Panics if `page_size` is zero.
+    pub fn new(query: Query, page_size: u32) -> Self {
+        assert!(page_size > 0, "page size must not be zero");
+
+        let mut paged_query = Self {
+            query,
+            page_size,
+            last_doc: None,
+            done: false,
+            pending: None,
+        };
+
+        paged_query.pending = Some(paged_query.fetch_page());
+
+        paged_query
+    }
+
+    /// Fetches the next page, returning `None` once there are no more.
+    pub async fn next_page(&mut self) -> Result<Option<Vec<DocumentSnapshot>>, FirestoreError> {
+        self.next().await.transpose()
+    }
+
+    fn fetch_page(&self) -> JsFuture {
+        let mut constraints = vec![limit(self.page_size as i32)];
+
+        if let Some(last_doc) = &self.last_doc {
+            constraints.push(start_after_snapshot(last_doc));
+        }
+
+        b::get_docs_promise(b::query_with(self.query.clone(), constraints)).into()
+    }
+}
+
+impl Stream for PagedQuery {
+    type Item = Result<Vec<DocumentSnapshot>, FirestoreError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        let mut pending = this.pending.take().unwrap_or_else(|| this.fetch_page());
+
+        let page = match pending.poll_unpin(cx) {
+            Poll::Ready(page) => page,
+            Poll::Pending => {
+                this.pending = Some(pending);
+
+                return Poll::Pending;
+            }
+        };
+
+        match page {
+            Ok(page) => {
+                let docs = page.unchecked_into::<QuerySnapshot>().docs();
+
+                this.done = docs.len() < this.page_size as usize;
+                this.last_doc = docs.last().cloned();
+
+                if docs.is_empty() {
+                    Poll::Ready(None)
+                } else {
+                    Poll::Ready(Some(Ok(docs)))
+                }
+            }
+            Err(err) => {
+                this.done = true;
+
+                Poll::Ready(Some(Err(err.unchecked_into::<FirebaseError>().into())))
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use super::{
+    bindings as b, get_docs, limit, order_by, query_snapshots, start_after_snapshot, where_,
+    CollectionReference, Direction, DocumentSnapshot, FieldPath, FirestoreError, Query,
+    QueryConstraint, QueryConstraintOp, QuerySnapshot, SnapshotStream,
+};
+use wasm_bindgen::JsValue;
+
+/// Builds a [`Query`] by chaining constraints.
+///
+/// Cloning a partially built query is cheap, so a base query can be
+/// shared, for example between a list and its count.
+#[derive(Clone, Debug)]
+pub struct QueryBuilder {
+    query: Query,
+    constraints: Vec<QueryConstraint>,
+}
+
+impl QueryBuilder {
+    /// Starts building on top of `query`, which may also be a
+    /// [`CollectionReference`].
+    pub fn new(query: impl Into<Query>) -> Self {
+        Self {
+            query: query.into(),
+            constraints: Vec::new(),
+        }
+    }
+
+    pub fn where_<F: Into<FieldPath>, V: Into<JsValue>>(
+        self,
+        field_path: F,
+        op: QueryConstraintOp,
+        value: V,
+    ) -> Self {
+        self.constraint(where_(field_path, op, value))
+    }
+
+    pub fn order_by(self, field_path: &str, direction: Direction) -> Self {
+        self.constraint(order_by(field_path, direction))
+    }
+
+    pub fn limit(self, number: i32) -> Self {
+        self.constraint(limit(number))
+    }
+
+    /// Starts the results after `snapshot`, which should be the last
+    /// document of the previous page.
+    pub fn start_after_doc(self, snapshot: &DocumentSnapshot) -> Self {
+        self.constraint(start_after_snapshot(snapshot))
+    }
+
+    /// Adds any other constraint, such as one built with [`or_`](super::or_).
+    pub fn constraint(mut self, constraint: QueryConstraint) -> Self {
+        self.constraints.push(constraint);
+
+        self
+    }
+
+    /// Applies `f` only if `condition` is true, which is handy for
+    /// optional filters.
+    pub fn when(self, condition: bool, f: impl FnOnce(Self) -> Self) -> Self {
+        if condition {
+            f(self)
+        } else {
+            self
+        }
+    }
+
+    pub fn build(self) -> Query {
+        if self.constraints.is_empty() {
+            self.query
+        } else {
+            b::query_with(self.query, self.constraints)
+        }
+    }
+
+    pub async fn get(self) -> Result<QuerySnapshot, FirestoreError> {
+        get_docs(self.build()).await
+    }
+
+    /// Listens to the results of the query.
+    pub fn stream(self) -> SnapshotStream<QuerySnapshot> {
+        query_snapshots(self.build())
+    }
+}
+
+impl From<CollectionReference> for QueryBuilder {
+    fn from(collection: CollectionReference) -> Self {
+        Self::new(collection)
+    }
+}
+
+impl From<Query> for QueryBuilder {
+    fn from(query: Query) -> Self {
+        Self::new(query)
+    }
+}
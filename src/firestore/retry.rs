@@ -0,0 +1,206 @@
+use super::{
+    get_doc, get_docs, query_snapshots, set_doc, DocumentReference, DocumentSnapshot,
+    FirestoreError, Query, QuerySnapshot, SnapshotStream,
+};
+use futures::{channel::oneshot, future::LocalBoxFuture, Future, FutureExt, Stream, StreamExt};
+use gloo::timers::callback::Timeout;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use wasm_bindgen::JsValue;
+
+/// How [`with_retry`] retries failed operations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The most times to run the operation, including the first attempt.
+    pub max_attempts: u32,
+    /// The delay before the first retry, which doubles on every retry.
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff with full jitter.
+    fn delay(&self, retry: u32) -> Duration {
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+
+        backoff.mul_f64(js_sys::Math::random())
+    }
+}
+
+/// Runs `op`, retrying with backoff while it fails with an error which
+/// [`is_retryable`](FirestoreError::is_retryable).
+///
+/// Other errors, such as [`FirestoreErrorKind::PermissionDenied`] or
+/// [`FirestoreErrorKind::NotFound`], are returned right away.
+///
+/// [`FirestoreErrorKind::PermissionDenied`]: super::FirestoreErrorKind::PermissionDenied
+/// [`FirestoreErrorKind::NotFound`]: super::FirestoreErrorKind::NotFound
+pub async fn with_retry<T, Fut>(
+    policy: RetryPolicy,
+    mut op: impl FnMut() -> Fut,
+) -> Result<T, FirestoreError>
+where
+    Fut: Future<Output = Result<T, FirestoreError>>,
+{
+    let mut attempt = 1;
+
+    loop {
+        match op().await {
+            Err(err) if err.is_retryable() && attempt < policy.max_attempts => {
+                trace!(attempt, "retrying firestore operation after error: {err}");
+
+                sleep(policy.delay(attempt - 1)).await;
+
+                attempt += 1;
+            }
+            res => return res,
+        }
+    }
+}
+
+pub async fn get_doc_retry(
+    policy: RetryPolicy,
+    doc: DocumentReference,
+) -> Result<Option<DocumentSnapshot>, FirestoreError> {
+    with_retry(policy, || get_doc(doc.clone())).await
+}
+
+pub async fn get_docs_retry(
+    policy: RetryPolicy,
+    query: Query,
+) -> Result<QuerySnapshot, FirestoreError> {
+    with_retry(policy, || get_docs(query.clone())).await
+}
+
+pub async fn set_doc_retry<D: Into<JsValue>>(
+    policy: RetryPolicy,
+    doc: DocumentReference,
+    data: D,
+) -> Result<(), FirestoreError> {
+    let data = data.into();
+
+    with_retry(policy, || set_doc(doc.clone(), data.clone())).await
+}
+
+/// An item of [`resilient_query_snapshots`].
+#[derive(Debug)]
+pub enum ListenerEvent<T> {
+    Snapshot(T),
+    /// The listener failed with a retryable `error`, and is resubscribing
+    /// after a backoff. Resubscribing restarts with a full snapshot.
+    Reconnecting {
+        attempt: u32,
+        error: FirestoreError,
+    },
+}
+
+/// Same as [`query_snapshots`], but resubscribes with backoff after
+/// errors which [`is_retryable`](FirestoreError::is_retryable).
+///
+/// Other errors, or running out of `policy.max_attempts` in a row without
+/// a snapshot in between, still end the stream after yielding the error.
+pub fn resilient_query_snapshots(query: Query, policy: RetryPolicy) -> ResilientQuerySnapshots {
+    ResilientQuerySnapshots {
+        listener: Some(query_snapshots(query.clone())),
+        query,
+        policy,
+        backoff: None,
+        failures: 0,
+        done: false,
+    }
+}
+
+/// See [`resilient_query_snapshots`].
+pub struct ResilientQuerySnapshots {
+    query: Query,
+    policy: RetryPolicy,
+    listener: Option<SnapshotStream<QuerySnapshot>>,
+    backoff: Option<LocalBoxFuture<'static, ()>>,
+    failures: u32,
+    done: bool,
+}
+
+impl Stream for ResilientQuerySnapshots {
+    type Item = Result<ListenerEvent<QuerySnapshot>, FirestoreError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        if let Some(backoff) = &mut this.backoff {
+            if backoff.poll_unpin(cx).is_pending() {
+                return Poll::Pending;
+            }
+
+            this.backoff = None;
+        }
+
+        let listener = this
+            .listener
+            .get_or_insert_with(|| query_snapshots(this.query.clone()));
+
+        match listener.poll_next_unpin(cx) {
+            Poll::Ready(Some(Ok(snapshot))) => {
+                this.failures = 0;
+
+                Poll::Ready(Some(Ok(ListenerEvent::Snapshot(snapshot))))
+            }
+            Poll::Ready(Some(Err(err)))
+                if err.is_retryable() && this.failures + 1 < this.policy.max_attempts =>
+            {
+                trace!("reconnecting snapshot listener after error: {err}");
+
+                this.listener = None;
+                this.backoff = Some(sleep(this.policy.delay(this.failures)).boxed_local());
+                this.failures += 1;
+
+                Poll::Ready(Some(Ok(ListenerEvent::Reconnecting {
+                    attempt: this.failures,
+                    error: err,
+                })))
+            }
+            Poll::Ready(Some(Err(err))) => {
+                this.done = true;
+
+                Poll::Ready(Some(Err(err)))
+            }
+            Poll::Ready(None) => {
+                this.done = true;
+
+                Poll::Ready(None)
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+pub(crate) async fn sleep(duration: Duration) {
+    let (tx, rx) = oneshot::channel();
+
+    let millis = duration.as_millis().try_into().unwrap_or(u32::MAX);
+
+    let _timeout = Timeout::new(millis, move || {
+        let _ = tx.send(());
+    });
+
+    let _ = rx.await;
+}
@@ -0,0 +1,384 @@
+use super::{
+    bindings as b,
+    metrics::{self, OpKind},
+    DocumentReference, DocumentSnapshot, FirestoreError, Query, QuerySnapshot, SnapshotMetadata,
+};
+use crate::FirebaseError;
+use futures::{Stream, StreamExt};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    marker::PhantomData,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+use wasm_bindgen::{prelude::*, JsCast};
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SnapshotStreamOptions {
+    /// Also yield snapshots when only their [`SnapshotMetadata`] changed,
+    /// such as when a cached snapshot is confirmed by the server.
+    pub include_metadata_changes: bool,
+    /// Keep the stream open after a
+    /// [`PermissionDenied`](super::FirestoreErrorKind::PermissionDenied)
+    /// error, such as one caused by the user signing out, so that the
+    /// caller can [`resubscribe`](SnapshotStream::resubscribe) once they
+    /// are allowed to read again.
+    ///
+    /// Firestore still stops listening after the error, so the stream
+    /// yields nothing more until it is resubscribed.
+    pub auto_retry_on_permission_error: bool,
+}
+
+impl SnapshotStreamOptions {
+    fn to_js(self) -> JsValue {
+        let options = js_sys::Object::new();
+
+        js_sys::Reflect::set(
+            &options,
+            &"includeMetadataChanges".into(),
+            &self.include_metadata_changes.into(),
+        )
+        .unwrap();
+
+        options.into()
+    }
+}
+
+/// Listens to snapshots of a document.
+pub fn doc_snapshots(doc: DocumentReference) -> SnapshotStream<DocumentSnapshot> {
+    doc_snapshots_with_options(doc, SnapshotStreamOptions::default())
+}
+
+pub fn doc_snapshots_with_options(
+    doc: DocumentReference,
+    options: SnapshotStreamOptions,
+) -> SnapshotStream<DocumentSnapshot> {
+    SnapshotStream::new(&doc, options)
+}
+
+/// Listens to snapshots of the results of a query.
+pub fn query_snapshots(query: Query) -> SnapshotStream<QuerySnapshot> {
+    query_snapshots_with_options(query, SnapshotStreamOptions::default())
+}
+
+pub fn query_snapshots_with_options(
+    query: Query,
+    options: SnapshotStreamOptions,
+) -> SnapshotStream<QuerySnapshot> {
+    SnapshotStream::new(&query, options)
+}
+
+/// A snapshot which carries [`SnapshotMetadata`].
+pub trait Snapshot {
+    fn snapshot_metadata(&self) -> SnapshotMetadata;
+}
+
+impl Snapshot for DocumentSnapshot {
+    fn snapshot_metadata(&self) -> SnapshotMetadata {
+        self.metadata()
+    }
+}
+
+impl Snapshot for QuerySnapshot {
+    fn snapshot_metadata(&self) -> SnapshotMetadata {
+        self.metadata()
+    }
+}
+
+/// A [`Stream`] of snapshots from an `onSnapshot` listener.
+///
+/// Firestore stops listening after an error, so the stream ends after
+/// yielding its first error, unless it is a permission error and
+/// [`SnapshotStreamOptions::auto_retry_on_permission_error`] is set.
+pub struct SnapshotStream<T> {
+    on_next: Closure<dyn FnMut(JsValue)>,
+    on_err: Closure<dyn FnMut(JsValue)>,
+    reference: JsValue,
+    options: SnapshotStreamOptions,
+    items: Rc<RefCell<VecDeque<Result<T, FirestoreError>>>>,
+    completed: Rc<RefCell<bool>>,
+    waker: Rc<RefCell<Option<Waker>>>,
+    unsub: js_sys::Function,
+    _snapshot: PhantomData<T>,
+}
+
+impl<T: JsCast + 'static> SnapshotStream<T> {
+    fn new(reference: &JsValue, options: SnapshotStreamOptions) -> Self {
+        let waker: Rc<RefCell<Option<Waker>>> = Rc::default();
+        let completed: Rc<RefCell<bool>> = Rc::default();
+        let items: Rc<RefCell<VecDeque<Result<T, FirestoreError>>>> = Rc::default();
+
+        let listened = reference.clone();
+
+        let on_next = Closure::new(clone!([items, waker], move |snapshot: JsValue| {
+            metrics::record(OpKind::ListenerUpdate, || {
+                let documents = snapshot
+                    .dyn_ref::<QuerySnapshot>()
+                    .map_or(1, QuerySnapshot::size);
+
+                (metrics::path_of(&listened), documents)
+            });
+
+            items.borrow_mut().push_back(Ok(snapshot.unchecked_into()));
+
+            if let Some(w) = waker.borrow().as_ref() {
+                w.wake_by_ref();
+            }
+        }));
+        let on_err = Closure::new(clone!([items, completed, waker], move |err: JsValue| {
+            let err = FirestoreError::from(err.unchecked_into::<FirebaseError>());
+
+            error!("snapshot listener failed: {err}");
+
+            // The listener is cancelled after an error, but may be
+            // resubscribed after a permission error
+            if !(options.auto_retry_on_permission_error && err.is_permission_denied()) {
+                *completed.borrow_mut() = true;
+            }
+
+            items.borrow_mut().push_back(Err(err));
+
+            if let Some(w) = waker.borrow().as_ref() {
+                w.wake_by_ref();
+            }
+        }));
+
+        let unsub = b::on_snapshot_with_options(reference, &options.to_js(), &on_next, &on_err);
+
+        Self {
+            on_next,
+            on_err,
+            reference: reference.clone(),
+            options,
+            items,
+            completed,
+            waker,
+            unsub,
+            _snapshot: PhantomData,
+        }
+    }
+}
+
+impl<T> SnapshotStream<T> {
+    /// Starts listening again, such as after a permission error with
+    /// [`SnapshotStreamOptions::auto_retry_on_permission_error`].
+    ///
+    /// Does nothing once the stream has ended.
+    pub fn resubscribe(&mut self) {
+        if *self.completed.borrow() {
+            return;
+        }
+
+        trace!("resubscribing snapshot listener");
+
+        let _ = self.unsub.call0(&JsValue::UNDEFINED);
+
+        self.unsub = b::on_snapshot_with_options(
+            &self.reference,
+            &self.options.to_js(),
+            &self.on_next,
+            &self.on_err,
+        );
+    }
+}
+
+impl<T: Snapshot> SnapshotStream<T> {
+    /// Skips snapshots from the local cache until the first snapshot from
+    /// the server arrives, after which everything is passed through.
+    ///
+    /// If the server agrees with the cache, Firestore only reports this as
+    /// a metadata change, so listen with
+    /// [`include_metadata_changes`](SnapshotStreamOptions::include_metadata_changes)
+    /// or the first server snapshot might never arrive.
+    pub fn skip_cached_initial(self) -> SkipCachedInitial<Self> {
+        SkipCachedInitial {
+            stream: self,
+            from_server: false,
+        }
+    }
+}
+
+impl<T> Drop for SnapshotStream<T> {
+    fn drop(&mut self) {
+        let _ = self.unsub.call0(&JsValue::UNDEFINED);
+    }
+}
+
+impl<T> Stream for SnapshotStream<T> {
+    type Item = Result<T, FirestoreError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // Update waker
+        *self.waker.borrow_mut() = Some(cx.waker().to_owned());
+
+        if let Some(item) = self.items.borrow_mut().pop_front() {
+            Poll::Ready(Some(item))
+        } else if *self.completed.borrow() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// See [`SnapshotStream::skip_cached_initial`].
+pub struct SkipCachedInitial<S> {
+    stream: S,
+    from_server: bool,
+}
+
+impl<S, T> Stream for SkipCachedInitial<S>
+where
+    S: Stream<Item = Result<T, FirestoreError>> + Unpin,
+    T: Snapshot,
+{
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.stream.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(snapshot))) if !self.from_server => {
+                    if !snapshot.snapshot_metadata().from_cache() {
+                        self.from_server = true;
+
+                        return Poll::Ready(Some(Ok(snapshot)));
+                    }
+                }
+                poll => return poll,
+            }
+        }
+    }
+}
+
+/// The paths of documents which changed between two [`QuerySnapshot`]s.
+///
+/// Paths are used rather than IDs since documents from a collection group
+/// query can share an ID.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    pub added: Vec<String>,
+    pub modified: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// Computes which documents were added, modified or removed between
+/// `prev` and `next`, such as two results of polling a query with
+/// [`get_docs`](super::get_docs).
+///
+/// Documents are compared by their data and [`SnapshotMetadata`], so a
+/// document which was confirmed by the server also counts as modified.
+pub fn diff_snapshots(prev: &QuerySnapshot, next: &QuerySnapshot) -> SnapshotDiff {
+    fn doc_path(doc: &DocumentSnapshot) -> String {
+        doc.ref_().path()
+    }
+
+    let next_docs = next.docs();
+
+    if prev.empty() {
+        return SnapshotDiff {
+            added: next_docs.iter().map(doc_path).collect(),
+            ..Default::default()
+        };
+    }
+
+    let mut prev_docs = prev
+        .docs()
+        .into_iter()
+        .map(|doc| (doc_path(&doc), doc))
+        .collect::<HashMap<_, _>>();
+
+    let mut diff = SnapshotDiff::default();
+
+    for doc in next_docs {
+        let path = doc_path(&doc);
+
+        match prev_docs.remove(&path) {
+            Some(prev_doc) if prev_doc == doc => {}
+            Some(_) => diff.modified.push(path),
+            None => diff.added.push(path),
+        }
+    }
+
+    // Keep the results deterministic
+    diff.removed = prev
+        .docs()
+        .iter()
+        .map(doc_path)
+        .filter(|path| prev_docs.contains_key(path))
+        .collect();
+
+    diff
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod wasm_tests {
+    use super::*;
+    use crate::firestore::FirestoreErrorKind;
+    use futures::stream;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    struct FakeSnapshot {
+        id: u32,
+        metadata: SnapshotMetadata,
+    }
+
+    impl Snapshot for FakeSnapshot {
+        fn snapshot_metadata(&self) -> SnapshotMetadata {
+            self.metadata.clone()
+        }
+    }
+
+    fn snapshot(id: u32, from_cache: bool) -> Result<FakeSnapshot, FirestoreError> {
+        let metadata = js_sys::Object::new();
+
+        js_sys::Reflect::set(&metadata, &"fromCache".into(), &from_cache.into()).unwrap();
+
+        Ok(FakeSnapshot {
+            id,
+            metadata: metadata.unchecked_into(),
+        })
+    }
+
+    fn skip_cached_initial(
+        snapshots: Vec<Result<FakeSnapshot, FirestoreError>>,
+    ) -> Vec<Result<u32, FirestoreErrorKind>> {
+        let stream = SkipCachedInitial {
+            stream: stream::iter(snapshots),
+            from_server: false,
+        };
+
+        futures::executor::block_on(stream.collect::<Vec<_>>())
+            .into_iter()
+            .map(|snapshot| snapshot.map(|snapshot| snapshot.id).map_err(|err| err.kind))
+            .collect()
+    }
+
+    #[wasm_bindgen_test]
+    fn skips_cached_snapshots_until_the_first_from_the_server() {
+        let snapshots = vec![
+            snapshot(1, true),
+            snapshot(2, true),
+            snapshot(3, false),
+            snapshot(4, true),
+            snapshot(5, false),
+        ];
+
+        assert_eq!(skip_cached_initial(snapshots), [Ok(3), Ok(4), Ok(5)]);
+    }
+
+    #[wasm_bindgen_test]
+    fn passes_errors_through_before_the_first_server_snapshot() {
+        let snapshots = vec![
+            snapshot(1, true),
+            Err(FirestoreError::new("unavailable", "offline")),
+            snapshot(2, false),
+        ];
+
+        assert_eq!(
+            skip_cached_initial(snapshots),
+            [Err(FirestoreErrorKind::Unavailable), Ok(2)],
+        );
+    }
+}
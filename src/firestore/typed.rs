@@ -0,0 +1,164 @@
+use super::{
+    add_doc, data::to_value, delete_doc, doc_snapshots, get_doc_typed, get_docs, query_snapshots,
+    set_doc_serde, update_doc, CollectionReference, DataError, DocumentReference, FirestoreError,
+};
+use futures::{Stream, StreamExt};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+/// A [`CollectionReference`] whose documents are all of type `T`.
+pub struct TypedCollection<T> {
+    reference: CollectionReference,
+    _data: PhantomData<T>,
+}
+
+impl<T> TypedCollection<T> {
+    pub fn new(reference: CollectionReference) -> Self {
+        Self {
+            reference,
+            _data: PhantomData,
+        }
+    }
+
+    pub fn reference(&self) -> &CollectionReference {
+        &self.reference
+    }
+
+    pub fn doc(&self, id: &str) -> Result<TypedDocRef<T>, FirestoreError> {
+        self.reference.doc_with_id(id).map(TypedDocRef::new)
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> TypedCollection<T> {
+    pub async fn add(&self, value: &T) -> Result<TypedDocRef<T>, FirestoreError> {
+        add_doc(self.reference.clone(), to_value(value)?)
+            .await
+            .map(TypedDocRef::new)
+    }
+
+    /// Reads every document in the collection.
+    pub async fn get(&self) -> Result<Vec<(TypedDocRef<T>, T)>, DataError> {
+        get_docs(self.reference.clone().into())
+            .await?
+            .docs()
+            .into_iter()
+            .map(|snapshot| {
+                let value = serde_wasm_bindgen::from_value(snapshot.data())
+                    .map_err(DataError::Deserialize)?;
+
+                Ok((TypedDocRef::new(snapshot.ref_()), value))
+            })
+            .collect()
+    }
+
+    pub fn snapshots(&self) -> impl Stream<Item = Result<Vec<T>, DataError>> {
+        query_snapshots(self.reference.clone().into()).map(|snapshot| {
+            snapshot?
+                .docs()
+                .into_iter()
+                .map(|doc| serde_wasm_bindgen::from_value(doc.data()))
+                .collect::<Result<_, _>>()
+                .map_err(DataError::Deserialize)
+        })
+    }
+}
+
+impl<T> Clone for TypedCollection<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.reference.clone())
+    }
+}
+
+impl<T> fmt::Debug for TypedCollection<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TypedCollection")
+            .field(&self.reference)
+            .finish()
+    }
+}
+
+impl<T> From<TypedCollection<T>> for CollectionReference {
+    fn from(collection: TypedCollection<T>) -> Self {
+        collection.reference
+    }
+}
+
+/// A [`DocumentReference`] to a document of type `T`.
+pub struct TypedDocRef<T> {
+    reference: DocumentReference,
+    _data: PhantomData<T>,
+}
+
+impl<T> TypedDocRef<T> {
+    pub fn new(reference: DocumentReference) -> Self {
+        Self {
+            reference,
+            _data: PhantomData,
+        }
+    }
+
+    pub fn reference(&self) -> &DocumentReference {
+        &self.reference
+    }
+
+    pub async fn delete(&self) -> Result<(), FirestoreError> {
+        delete_doc(self.reference.clone()).await
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> TypedDocRef<T> {
+    /// Reads the document, returning `None` if it does not exist.
+    pub async fn get(&self) -> Result<Option<T>, DataError> {
+        get_doc_typed(self.reference.clone()).await
+    }
+
+    pub async fn set(&self, value: &T) -> Result<(), FirestoreError> {
+        set_doc_serde(self.reference.clone(), value, None).await
+    }
+
+    /// Same as [`set`](Self::set), but fails if the document does not exist.
+    pub async fn update(&self, value: &T) -> Result<(), FirestoreError> {
+        update_doc(self.reference.clone(), to_value(value)?).await
+    }
+
+    /// Listens to the document, yielding `None` while it does not exist.
+    pub fn snapshots(&self) -> impl Stream<Item = Result<Option<T>, DataError>> {
+        doc_snapshots(self.reference.clone()).map(|snapshot| snapshot?.data_typed())
+    }
+}
+
+impl<T> Clone for TypedDocRef<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.reference.clone())
+    }
+}
+
+impl<T> fmt::Debug for TypedDocRef<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TypedDocRef").field(&self.reference).finish()
+    }
+}
+
+impl<T> PartialEq for TypedDocRef<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.reference == other.reference
+    }
+}
+
+impl<T> Eq for TypedDocRef<T> {}
+
+impl<T> Hash for TypedDocRef<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.reference.hash(state);
+    }
+}
+
+impl<T> From<TypedDocRef<T>> for DocumentReference {
+    fn from(doc: TypedDocRef<T>) -> Self {
+        doc.reference
+    }
+}
@@ -0,0 +1,62 @@
+use super::{bindings as b, data::to_value, DocumentReference, FieldPath, FirestoreError};
+use crate::FirebaseError;
+use serde::Serialize;
+use wasm_bindgen::{JsCast, JsValue};
+
+/// Builds a single `updateDoc` call from a list of field/value pairs.
+///
+/// Values can be anything implementing [`Serialize`], including sentinels
+/// such as [`server_timestamp`](super::server_timestamp).
+#[derive(Clone, Debug, Default)]
+pub struct Update {
+    fields_and_values: Vec<JsValue>,
+    err: Option<FirestoreError>,
+}
+
+impl Update {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the dot-separated field `field`, such as `address.city`.
+    pub fn field<V: Serialize>(self, field: &str, value: V) -> Self {
+        self.push(field.into(), &value)
+    }
+
+    pub fn field_path<V: Serialize>(self, field: FieldPath, value: V) -> Self {
+        self.push(field.into(), &value)
+    }
+
+    fn push<V: Serialize>(mut self, field: JsValue, value: &V) -> Self {
+        if self.err.is_none() {
+            match to_value(value) {
+                Ok(value) => self.fields_and_values.extend([field, value]),
+                Err(err) => self.err = Some(err),
+            }
+        }
+
+        self
+    }
+
+    /// Applies the update to `doc`, failing if no fields were set or the
+    /// document does not exist.
+    pub async fn apply(self, doc: DocumentReference) -> Result<(), FirestoreError> {
+        if let Some(err) = self.err {
+            return Err(err);
+        }
+
+        let mut fields_and_values = self.fields_and_values.into_iter();
+
+        let (Some(field), Some(value)) = (fields_and_values.next(), fields_and_values.next())
+        else {
+            return Err(FirestoreError::new(
+                "invalid-argument",
+                "an update must set at least one field",
+            ));
+        };
+
+        b::update_doc_fields(doc, field, value, fields_and_values.collect())
+            .await
+            .map_err(|err| err.unchecked_into::<FirebaseError>().into())
+    }
+}
@@ -1,3 +1,4 @@
+use crate::app::FirebaseApp;
 use serde::{Deserialize, Serialize};
 use std::{future::Future, marker::PhantomData};
 use wasm_bindgen::{prelude::*, JsCast};
@@ -110,7 +111,10 @@ extern "C" {
     pub type Functions;
 
     #[wasm_bindgen(js_name = getFunctions)]
-    pub fn get_functions() -> Functions;
+    pub fn get_functions(app: &FirebaseApp) -> Functions;
+
+    #[wasm_bindgen(js_name = getFunctions)]
+    pub fn get_functions_default() -> Functions;
 
     #[wasm_bindgen(js_name = httpsCallable)]
     fn https_callable_(
@@ -7,11 +7,24 @@
 //! A good example of this can be found in the [`UploadTask`](storage::UploadTask)
 //! struct, which is a helpful wrapper for converting upload tasks into
 //! rust [`streams`](futures::Stream).
+//!
+//! Each Firebase product lives behind a Cargo feature of the same name
+//! (`auth`, `firestore`, `functions` and `storage`), all of which are
+//! enabled by default. Disable the default features and pick the ones
+//! you need to keep unused bindings out of your binary, or enable
+//! `full` to get everything, including the Realtime Database (`rtdb`).
+//! The `analytics` and `remote_config` features are reserved, and don't
+//! enable anything yet.
+//!
+//! Enable the `tracing` feature to have listener errors and other SDK
+//! activity reported through [`tracing`](https://docs.rs/tracing), such
+//! as to the browser console with `tracing-wasm`.
 
 #![cfg_attr(feature = "nightly", feature(unboxed_closures, fn_traits))]
 
 #[macro_use]
 extern crate clone_macro;
+#[cfg(feature = "tracing")]
 #[allow(unused_imports)]
 #[macro_use]
 extern crate tracing;
@@ -22,43 +35,56 @@ extern crate wasm_bindgen_struct;
 
 #[macro_use]
 mod utils;
+pub mod app;
+#[cfg(feature = "auth")]
 pub mod auth;
+#[cfg(feature = "rtdb")]
+pub mod database;
+#[cfg(feature = "firestore")]
 pub mod firestore;
+#[cfg(feature = "functions")]
 pub mod functions;
+#[cfg(feature = "storage")]
 pub mod storage;
+pub mod stream;
+#[cfg(all(test, target_arch = "wasm32"))]
+mod test_utils;
 
 use std::{error::Error, fmt};
 use wasm_bindgen::prelude::*;
 
-pub mod app {
-    use wasm_bindgen::prelude::*;
-
-    #[wasm_bindgen(module = "firebase/app")]
-    extern "C" {
-        #[derive(Clone, Debug)]
-        pub type FirebaseApp;
-
-        #[wasm_bindgen(js_name = "getApp")]
-        pub fn get_app() -> FirebaseApp;
+impl fmt::Display for FirebaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.message().fmt(f)
     }
 }
 
-impl fmt::Display for FirebaseError {
+impl fmt::Debug for FirebaseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.message().fmt(f)
+        f.debug_struct("FirebaseError")
+            .field("name", &self.name())
+            .field("code", &self.code())
+            .field("message", &self.message())
+            .finish()
     }
 }
 
 impl Error for FirebaseError {}
 
-#[wasm_bindgen]
+#[wasm_bindgen(module = "firebase/app")]
 extern "C" {
-    #[derive(Clone, Debug)]
+    #[derive(Clone)]
     pub type FirebaseError;
 
+    #[wasm_bindgen(constructor)]
+    pub(crate) fn new(code: &str, message: &str) -> FirebaseError;
+
     #[wasm_bindgen(method, getter)]
     pub fn code(this: &FirebaseError) -> String;
 
     #[wasm_bindgen(method, getter)]
     pub fn message(this: &FirebaseError) -> String;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn name(this: &FirebaseError) -> String;
 }
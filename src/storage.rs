@@ -1,34 +1,318 @@
 mod bindings;
 
+use crate::FirebaseError;
 pub use bindings::{
-    delete_object, get_download_url, get_storage, ref_, upload_bytes, FullMetadata, Ref,
-    SettableMetadata, Storage, UploadMetadata, UploadMetadataOptions, UploadTask,
+    get_storage, get_storage_default, ref_, upload_bytes, FullMetadata, ListResult, Ref,
+    SettableMetadata, Storage, UploadMetadata, UploadMetadataOptions, UploadResult, UploadTask,
     UploadTaskSnapshot,
 };
-use futures::Stream;
+use futures::{
+    stream::{self, FusedStream},
+    Future, Stream, StreamExt,
+};
+use serde::Serialize;
 use std::{
     cell::RefCell,
+    collections::VecDeque,
+    error::Error,
+    fmt,
     pin::Pin,
     rc::Rc,
     task::{Context, Poll, Waker},
 };
 use wasm_bindgen::{prelude::*, JsCast};
 
+/// An error from Firebase Storage.
+///
+/// This converts into a [`JsValue`], so it can be returned as the error
+/// of `#[wasm_bindgen]` functions and thrown to JS callers.
+#[derive(Clone, Debug, derive_more::Deref)]
+#[wasm_bindgen(getter_with_clone)]
+pub struct StorageError {
+    #[wasm_bindgen(skip)]
+    pub kind: StorageErrorKind,
+    #[deref]
+    #[wasm_bindgen(readonly)]
+    pub source: FirebaseError,
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.source.fmt(f)
+    }
+}
+
+impl Error for StorageError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl From<FirebaseError> for StorageError {
+    fn from(err: FirebaseError) -> Self {
+        let kind = StorageErrorKind::from_code(&err.code());
+
+        Self { kind, source: err }
+    }
+}
+
+impl TryFrom<JsValue> for StorageError {
+    type Error = JsValue;
+
+    /// Converts a JS error with a string `code` and `message`, such as the
+    /// SDK's `StorageError`, handing back anything else.
+    fn try_from(value: JsValue) -> Result<Self, Self::Error> {
+        let is_string = |key: &str| {
+            js_sys::Reflect::get(&value, &key.into()).map_or(false, |value| value.is_string())
+        };
+
+        if is_string("code") && is_string("message") {
+            Ok(value.unchecked_into::<FirebaseError>().into())
+        } else {
+            Err(value)
+        }
+    }
+}
+
+impl StorageError {
+    /// Converts an error thrown by the SDK, reading its `code` and
+    /// `message`.
+    ///
+    /// Anything which isn't a Firebase error becomes a
+    /// [`StorageErrorKind::Unknown`] error with the best message which can
+    /// be found.
+    pub fn from_js_error(val: JsValue) -> Self {
+        Self::try_from(val).unwrap_or_else(|val| {
+            let message = val
+                .dyn_ref::<js_sys::Error>()
+                .map(|err| String::from(err.message()))
+                .or_else(|| val.as_string())
+                .unwrap_or_else(|| format!("{val:?}"));
+
+            Self::new(StorageErrorKind::Unknown, &message)
+        })
+    }
+
+    /// Creates an error on the Rust side, for mistakes which can be caught
+    /// before calling into the SDK.
+    pub(crate) fn new(kind: StorageErrorKind, message: &str) -> Self {
+        let code = format!("storage/{kind}");
+
+        Self {
+            kind,
+            source: FirebaseError::new(&code, message),
+        }
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        self.kind == StorageErrorKind::ObjectNotFound
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, strum::EnumString, strum::IntoStaticStr)]
+#[non_exhaustive]
+pub enum StorageErrorKind {
+    #[strum(serialize = "unknown")]
+    Unknown,
+    #[strum(serialize = "object-not-found")]
+    ObjectNotFound,
+    #[strum(serialize = "bucket-not-found")]
+    BucketNotFound,
+    #[strum(serialize = "project-not-found")]
+    ProjectNotFound,
+    #[strum(serialize = "quota-exceeded")]
+    QuotaExceeded,
+    #[strum(serialize = "unauthenticated")]
+    Unauthenticated,
+    #[strum(serialize = "unauthorized")]
+    Unauthorized,
+    #[strum(serialize = "unauthorized-app")]
+    UnauthorizedApp,
+    #[strum(serialize = "retry-limit-exceeded")]
+    RetryLimitExceeded,
+    #[strum(serialize = "invalid-checksum")]
+    InvalidChecksum,
+    #[strum(serialize = "canceled")]
+    Canceled,
+    #[strum(serialize = "invalid-event-name")]
+    InvalidEventName,
+    #[strum(serialize = "invalid-url")]
+    InvalidUrl,
+    #[strum(serialize = "invalid-default-bucket")]
+    InvalidDefaultBucket,
+    #[strum(serialize = "no-default-bucket")]
+    NoDefaultBucket,
+    #[strum(serialize = "cannot-slice-blob")]
+    CannotSliceBlob,
+    #[strum(serialize = "server-file-wrong-size")]
+    ServerFileWrongSize,
+    #[strum(serialize = "invalid-argument")]
+    InvalidArgument,
+    #[strum(serialize = "invalid-format")]
+    InvalidFormat,
+    #[strum(serialize = "invalid-root-operation")]
+    InvalidRootOperation,
+    #[strum(serialize = "unsupported-environment")]
+    UnsupportedEnvironment,
+    #[strum(serialize = "internal-error")]
+    InternalError,
+    /// The path given to [`ref_validated`] is not a valid object path. This
+    /// never comes from the SDK.
+    #[strum(serialize = "invalid-path")]
+    InvalidPath,
+    /// The object is larger than the maximum size given to [`get_blob`] or
+    /// [`get_bytes`].
+    /// This never comes from the SDK.
+    #[strum(serialize = "download-size-exceeded")]
+    DownloadSizeExceeded,
+    /// A code which is not known to this crate.
+    #[strum(default)]
+    Unrecognized(String),
+}
+
+impl StorageErrorKind {
+    /// Parses an error code, with or without the `storage/` service
+    /// prefix. [`Unrecognized`](Self::Unrecognized) codes are kept without
+    /// the prefix, but the raw code is still available through
+    /// [`StorageError::source`].
+    pub fn from_code(code: &str) -> Self {
+        let unprefixed = code.strip_prefix("storage/").unwrap_or(code);
+
+        match unprefixed.parse() {
+            Ok(kind) => kind,
+            // Unreachable, since unknown codes parse to the default variant
+            Err(_) => Self::Unrecognized(unprefixed.to_owned()),
+        }
+    }
+
+    pub fn is_unrecognized(&self) -> bool {
+        matches!(self, Self::Unrecognized(_))
+    }
+}
+
+impl fmt::Display for StorageErrorKind {
+    /// Writes the error code without the `storage/` prefix, such as
+    /// `object-not-found`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unrecognized(code) => f.write_str(code),
+            kind => f.write_str(<&'static str>::from(kind)),
+        }
+    }
+}
+
+/// Same as [`ref_`], but fails with [`StorageErrorKind::InvalidPath`] if
+/// `path` is empty, starts or ends with a `/`, or contains `//`, rather
+/// than failing once the reference is used.
+pub fn ref_validated(storage: Storage, path: &str) -> Result<Ref, StorageError> {
+    match path_problem(path) {
+        Some(problem) => Err(StorageError::new(
+            StorageErrorKind::InvalidPath,
+            &format!("invalid storage path `{path}`: {problem}"),
+        )),
+        None => Ok(ref_(storage, path)),
+    }
+}
+
+fn path_problem(path: &str) -> Option<&'static str> {
+    if path.is_empty() {
+        Some("must not be empty")
+    } else if path.starts_with('/') {
+        Some("must not start with a `/`")
+    } else if path.ends_with('/') {
+        Some("must not end with a `/`")
+    } else if path.contains("//") {
+        Some("must not contain empty segments")
+    } else {
+        None
+    }
+}
+
 impl UploadTask {
+    /// Waits for the upload to finish, returning the final snapshot.
+    ///
+    /// This can be used alongside [`async_iter`](Self::async_iter).
+    /// Dropping the future does not cancel the upload; use
+    /// [`cancel`](Self::cancel) for that.
+    pub async fn finished(self) -> Result<UploadTaskSnapshot, StorageError> {
+        // The task is a thenable, which the promise adopts
+        wasm_bindgen_futures::JsFuture::from(js_sys::Promise::resolve(&self))
+            .await
+            .map(|snapshot| snapshot.unchecked_into())
+            .map_err(StorageError::from_js_error)
+    }
+
+    /// Follows the upload's [`progress`](UploadTaskSnapshot::progress),
+    /// only yielding when it increases.
+    ///
+    /// The stream always ends with `1.0` if the upload succeeds, or with
+    /// an error otherwise.
+    pub fn progress_stream(&self) -> impl Stream<Item = Result<f64, StorageError>> {
+        // `None` once the stream should end
+        let state = Some((self.async_iter(), None));
+
+        stream::unfold(state, |state| async move {
+            let (mut snapshots, mut last) = state?;
+
+            loop {
+                match snapshots.next().await {
+                    Some(Ok(snapshot)) => {
+                        let progress = snapshot.progress().max(last.unwrap_or(0.0));
+
+                        if last != Some(progress) {
+                            last = Some(progress);
+
+                            return Some((Ok(progress), Some((snapshots, last))));
+                        }
+                    }
+                    Some(Err(err)) => return Some((Err(err), None)),
+                    None => return (last != Some(1.0)).then_some((Ok(1.0), None)),
+                }
+            }
+        })
+    }
+
+    /// Streams every snapshot of the upload, in order. See
+    /// [`async_iter_with_buffering`](Self::async_iter_with_buffering) to
+    /// limit how many are kept between polls.
     pub fn async_iter(&self) -> UploadTaskAsyncIter {
+        self.async_iter_with_buffering(UploadBuffering::Unbounded)
+    }
+
+    pub fn async_iter_with_buffering(&self, buffering: UploadBuffering) -> UploadTaskAsyncIter {
+        let capacity = match buffering {
+            UploadBuffering::Unbounded => usize::MAX,
+            UploadBuffering::Bounded(capacity) => capacity.max(1),
+            UploadBuffering::Latest => 1,
+        };
+
         let waker: Rc<RefCell<Option<Waker>>> = Rc::default();
         let completed: Rc<RefCell<bool>> = Rc::default();
-        let snapshot: Rc<RefCell<Option<UploadTaskSnapshot>>> = Rc::default();
-        let err: Rc<RefCell<Option<JsValue>>> = Rc::default();
+        let snapshots: Rc<RefCell<VecDeque<UploadTaskSnapshot>>> = Rc::default();
+        let err: Rc<RefCell<Option<StorageError>>> = Rc::default();
+
+        let on_snapshot = Closure::new(clone!([snapshots, waker], move |js_snapshot| {
+            let mut snapshots = snapshots.borrow_mut();
 
-        let on_snapshot = Closure::new(clone!([snapshot, waker], move |js_snapshot| {
-            *snapshot.borrow_mut() = Some(js_snapshot);
+            // Coalesce into the newest snapshot, so the latest progress is
+            // never lost
+            if snapshots.len() >= capacity {
+                snapshots.pop_back();
+            }
+
+            snapshots.push_back(js_snapshot);
+
+            drop(snapshots);
 
             if let Some(w) = waker.borrow().as_ref() {
                 w.wake_by_ref();
             }
         }));
         let on_err = Closure::new(clone!([completed, err, waker], move |js_err| {
+            let js_err = StorageError::from_js_error(js_err);
+
+            error!("upload failed: {js_err}");
+
             *err.borrow_mut() = Some(js_err);
 
             // Complete the stream since we errored
@@ -60,55 +344,663 @@ impl UploadTask {
             _on_snapshot: on_snapshot,
             _on_err: on_err,
             _on_complete: on_complete,
-            snapshot,
+            snapshots,
             err,
             completed,
             waker,
             unsub,
+            terminated: false,
         }
     }
 }
 
+/// The state of an upload, as reported by [`UploadTaskSnapshot::state`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum UploadState {
+    Running,
+    Paused,
+    Success,
+    Canceled,
+    Error,
+}
+
+impl UploadTaskSnapshot {
+    pub fn bytes_transferred(&self) -> u64 {
+        self.bytes_transferred_js() as u64
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.total_bytes_js() as u64
+    }
+
+    /// How much of the upload is done, from `0.0` to `1.0`.
+    ///
+    /// Empty uploads are reported as done.
+    pub fn progress(&self) -> f64 {
+        let total_bytes = self.total_bytes_js();
+
+        if total_bytes > 0.0 {
+            (self.bytes_transferred_js() / total_bytes).clamp(0.0, 1.0)
+        } else {
+            1.0
+        }
+    }
+
+    pub fn state(&self) -> UploadState {
+        match self.state_js().as_str() {
+            "running" => UploadState::Running,
+            "paused" => UploadState::Paused,
+            "success" => UploadState::Success,
+            "canceled" => UploadState::Canceled,
+            _ => UploadState::Error,
+        }
+    }
+}
+
+/// How many snapshots an [`UploadTaskAsyncIter`] keeps between polls.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum UploadBuffering {
+    /// Keep every snapshot.
+    #[default]
+    Unbounded,
+    /// Keep at most this many snapshots. Once full, a new snapshot
+    /// replaces the newest one which is kept, so the earlier state
+    /// changes and the latest progress are both delivered.
+    Bounded(usize),
+    /// Only keep the latest snapshot.
+    Latest,
+}
+
 pub struct UploadTaskAsyncIter {
     _on_snapshot: Closure<dyn FnMut(UploadTaskSnapshot)>,
     _on_err: Closure<dyn FnMut(JsValue)>,
     _on_complete: Closure<dyn FnMut()>,
-    snapshot: Rc<RefCell<Option<UploadTaskSnapshot>>>,
-    err: Rc<RefCell<Option<JsValue>>>,
+    snapshots: Rc<RefCell<VecDeque<UploadTaskSnapshot>>>,
+    err: Rc<RefCell<Option<StorageError>>>,
     completed: Rc<RefCell<bool>>,
     waker: Rc<RefCell<Option<Waker>>>,
     unsub: js_sys::Function,
+    terminated: bool,
 }
 
 impl Drop for UploadTaskAsyncIter {
     fn drop(&mut self) {
-        self.unsub.call0(&JsValue::UNDEFINED).unwrap();
+        // Panicking here could abort while unwinding
+        if let Err(err) = self.unsub.call0(&JsValue::UNDEFINED) {
+            error!("failed to unsubscribe from upload task: {err:?}");
+        }
     }
 }
 
 impl Stream for UploadTaskAsyncIter {
-    type Item = Result<UploadTaskSnapshot, JsValue>;
+    type Item = Result<UploadTaskSnapshot, StorageError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.terminated {
+            return Poll::Ready(None);
+        }
 
-    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         // Update waker
         *self.waker.borrow_mut() = Some(cx.waker().to_owned());
 
-        if *self.completed.borrow() {
-            if let Some(err) = self.err.borrow_mut().take() {
-                Poll::Ready(Some(Err(err)))
-            } else {
-                Poll::Ready(None)
-            }
-        } else if let Some(snapshot) = self.snapshot.borrow_mut().take() {
+        // Snapshots which arrived before completing are still yielded
+        let snapshot = self.snapshots.borrow_mut().pop_front();
+
+        if let Some(snapshot) = snapshot {
             Poll::Ready(Some(Ok(snapshot)))
+        } else if *self.completed.borrow() {
+            let err = self.err.borrow_mut().take();
+
+            if err.is_none() {
+                self.terminated = true;
+            }
+
+            Poll::Ready(err.map(Err))
         } else {
             Poll::Pending
         }
     }
 }
 
-pub async fn get_metadata(ref_: Ref) -> Result<FullMetadata, JsValue> {
+impl FusedStream for UploadTaskAsyncIter {
+    fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+}
+
+/// Gets a URL which anyone can use to download the object at `ref_`.
+pub async fn get_download_url(ref_: Ref) -> Result<String, StorageError> {
+    bindings::get_download_url(ref_)
+        .await
+        .map(|url| url.as_string().expect("download URL to be a string"))
+        .map_err(StorageError::from_js_error)
+}
+
+pub async fn delete_object(ref_: Ref) -> Result<(), StorageError> {
+    bindings::delete_object(ref_)
+        .await
+        .map_err(StorageError::from_js_error)
+}
+
+/// Gets the metadata of the object at `ref_`, without downloading it.
+pub async fn get_metadata(ref_: Ref) -> Result<FullMetadata, StorageError> {
     bindings::get_metadata(ref_)
         .await
         .map(|m| m.unchecked_into())
+        .map_err(StorageError::from_js_error)
+}
+
+/// How the string passed to [`upload_string`] is encoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StringFormat {
+    /// Uploaded as UTF-8.
+    Raw,
+    Base64,
+    Base64Url,
+    /// A `data:` URL, whose content type is used unless one is given in
+    /// the metadata.
+    DataUrl,
+}
+
+impl StringFormat {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Raw => "raw",
+            Self::Base64 => "base64",
+            Self::Base64Url => "base64url",
+            Self::DataUrl => "data_url",
+        }
+    }
+}
+
+/// Uploads `data`, decoded according to `format`, to `ref_`.
+///
+/// Malformed base64 or data URLs fail with
+/// [`StorageErrorKind::InvalidFormat`].
+pub async fn upload_string(
+    ref_: Ref,
+    data: &str,
+    format: StringFormat,
+    metadata: Option<UploadMetadataOptions>,
+) -> Result<UploadResult, StorageError> {
+    bindings::upload_string_(ref_, data, format.as_str(), metadata_to_js(metadata))
+        .await
+        .map(|result| result.unchecked_into())
+        .map_err(StorageError::from_js_error)
+}
+
+fn metadata_to_js(metadata: Option<UploadMetadataOptions>) -> JsValue {
+    metadata.map_or(JsValue::UNDEFINED, |metadata| metadata.to_js())
+}
+
+/// Starts uploading `data` to `ref_`, with its content type and other
+/// metadata taken from `metadata`.
+///
+/// Progress can be followed with [`UploadTask::async_iter`], and the
+/// metadata is included in the snapshots once the upload completes.
+pub fn upload_bytes_resumable(
+    ref_: Ref,
+    data: &[u8],
+    metadata: Option<UploadMetadataOptions>,
+) -> Result<UploadTask, StorageError> {
+    // Copy the data out of wasm memory, as the SDK reads it asynchronously
+    let data = js_sys::Uint8Array::from(data);
+
+    bindings::upload_uint8_array_resumable(ref_, &data, metadata_to_js(metadata))
+        .map_err(StorageError::from_js_error)
+}
+
+/// Uploads `data` to `ref_` in a single request, with its content type
+/// and other metadata taken from `metadata`.
+pub async fn upload_bytes_with_metadata(
+    ref_: Ref,
+    data: &[u8],
+    metadata: Option<UploadMetadataOptions>,
+) -> Result<UploadResult, StorageError> {
+    let data = js_sys::Uint8Array::from(data);
+
+    bindings::upload_uint8_array(ref_, &data, metadata_to_js(metadata))
+        .await
+        .map(|result| result.unchecked_into())
+        .map_err(StorageError::from_js_error)
+}
+
+/// Updates the metadata of the existing object at `ref_`, returning the
+/// metadata after the update.
+///
+/// Fields which are `None` in `metadata` are left as they are, and custom
+/// metadata is merged into the existing custom metadata, except for
+/// `remove_custom_keys`, which are removed.
+pub async fn update_metadata(
+    ref_: Ref,
+    metadata: UploadMetadataOptions,
+    remove_custom_keys: &[&str],
+) -> Result<FullMetadata, StorageError> {
+    let metadata = metadata.to_js();
+
+    if !remove_custom_keys.is_empty() {
+        let key = JsValue::from("customMetadata");
+
+        let custom_metadata = js_sys::Reflect::get(&metadata, &key)
+            .ok()
+            .filter(JsValue::is_object)
+            .unwrap_or_else(|| js_sys::Object::new().into());
+
+        // The SDK removes keys which are explicitly set to `null`
+        for remove_key in remove_custom_keys {
+            js_sys::Reflect::set(&custom_metadata, &(*remove_key).into(), &JsValue::NULL).unwrap();
+        }
+
+        js_sys::Reflect::set(&metadata, &key, &custom_metadata).unwrap();
+    }
+
+    bindings::update_metadata(ref_, metadata)
+        .await
+        .map(|m| m.unchecked_into())
+        .map_err(StorageError::from_js_error)
+}
+
+/// Lists every object and prefix directly under `ref_`.
+///
+/// This keeps requesting pages until everything has been listed, which
+/// can take a long time for large folders. See [`list`] to list them a
+/// page at a time.
+pub async fn list_all(ref_: Ref) -> Result<ListResult, StorageError> {
+    bindings::list_all(ref_)
+        .await
+        .map(|result| result.unchecked_into())
+        .map_err(StorageError::from_js_error)
+}
+
+#[serde_with::skip_serializing_none]
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListOptions {
+    /// How many results to return at most, between 1 and 1000. Defaults
+    /// to 1000.
+    pub max_results: Option<u32>,
+    /// The [`ListResult::next_page_token`] of the previous page.
+    pub page_token: Option<String>,
+}
+
+/// Lists a page of the objects and prefixes directly under `ref_`.
+///
+/// Fails with [`StorageErrorKind::InvalidArgument`] if
+/// [`ListOptions::max_results`] is outside of `1..=1000`.
+pub async fn list(ref_: Ref, options: ListOptions) -> Result<ListResult, StorageError> {
+    if let Some(max_results) = options.max_results {
+        if !(1..=1000).contains(&max_results) {
+            return Err(StorageError::new(
+                StorageErrorKind::InvalidArgument,
+                &format!("max_results must be between 1 and 1000, got {max_results}"),
+            ));
+        }
+    }
+
+    let options = serde_wasm_bindgen::to_value(&options).unwrap();
+
+    bindings::list(ref_, options)
+        .await
+        .map(|result| result.unchecked_into())
+        .map_err(StorageError::from_js_error)
+}
+
+/// Lists every object directly under `ref_`, requesting `page_size`
+/// objects at a time as the stream is polled.
+///
+/// Prefixes are skipped. The stream ends after the first error, and
+/// dropping it stops requesting further pages.
+pub fn list_stream(ref_: Ref, page_size: u32) -> impl Stream<Item = Result<Ref, StorageError>> {
+    list_pages(ref_, page_size, list)
+}
+
+/// Same as [`list_stream`], but requests each page with `list`, so that
+/// tests can mock it.
+fn list_pages<F, Fut>(
+    ref_: Ref,
+    page_size: u32,
+    mut list: F,
+) -> impl Stream<Item = Result<Ref, StorageError>>
+where
+    F: FnMut(Ref, ListOptions) -> Fut,
+    Fut: Future<Output = Result<ListResult, StorageError>>,
+{
+    // `None` once there are no more pages to request
+    let next_page = Some((ref_, None));
+
+    stream::unfold(next_page, move |next_page| {
+        let request = next_page.map(|(ref_, page_token)| {
+            let options = ListOptions {
+                max_results: Some(page_size),
+                page_token,
+            };
+
+            (list(ref_.clone(), options), ref_)
+        });
+
+        async move {
+            let (request, ref_) = request?;
+
+            match request.await {
+                Ok(page) => {
+                    let items = page.items().into_iter().map(Ok).collect::<Vec<_>>();
+
+                    let next_page = page.next_page_token().map(|token| (ref_, Some(token)));
+
+                    Some((stream::iter(items), next_page))
+                }
+                Err(err) => Some((stream::iter(vec![Err(err)]), None)),
+            }
+        }
+    })
+    .flatten()
+}
+
+/// Downloads the object at `ref_` through the SDK, which works even when
+/// download URLs are disabled, as long as the security rules allow it.
+///
+/// Fails with [`StorageErrorKind::DownloadSizeExceeded`] if the object
+/// is larger than `max_size` bytes.
+pub async fn get_blob(ref_: Ref, max_size: Option<u64>) -> Result<web_sys::Blob, StorageError> {
+    // The SDK silently truncates the download to the maximum size, so ask
+    // for one more byte to tell whether the object is larger
+    let blob = bindings::get_blob(ref_, max_size.map(|max_size| max_size as f64 + 1.0))
+        .await
+        .map_err(StorageError::from_js_error)?
+        .unchecked_into::<web_sys::Blob>();
+
+    check_download_size(blob.size(), max_size)?;
+
+    Ok(blob)
+}
+
+/// Downloads the object at `ref_` into wasm memory.
+///
+/// The object is first downloaded into JS memory and then copied once,
+/// so it briefly takes up twice its size; prefer [`get_blob`] for large
+/// objects which don't need to be processed in Rust. Fails with
+/// [`StorageErrorKind::DownloadSizeExceeded`] before copying anything if
+/// the object is larger than `max_size` bytes.
+pub async fn get_bytes(ref_: Ref, max_size: Option<u64>) -> Result<Vec<u8>, StorageError> {
+    // See `get_blob`
+    let buffer = bindings::get_bytes(ref_, max_size.map(|max_size| max_size as f64 + 1.0))
+        .await
+        .map_err(StorageError::from_js_error)?
+        .unchecked_into::<js_sys::ArrayBuffer>();
+
+    check_download_size(buffer.byte_length() as f64, max_size)?;
+
+    Ok(js_sys::Uint8Array::new(&buffer).to_vec())
+}
+
+fn check_download_size(size: f64, max_size: Option<u64>) -> Result<(), StorageError> {
+    match max_size {
+        Some(max_size) if size > max_size as f64 => Err(StorageError::new(
+            StorageErrorKind::DownloadSizeExceeded,
+            &format!("object is larger than the maximum of {max_size} bytes"),
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_code_strips_service_prefix() {
+        assert_eq!(
+            StorageErrorKind::from_code("storage/object-not-found"),
+            StorageErrorKind::ObjectNotFound,
+        );
+    }
+
+    #[test]
+    fn from_code_accepts_unprefixed_code() {
+        assert_eq!(
+            StorageErrorKind::from_code("object-not-found"),
+            StorageErrorKind::ObjectNotFound,
+        );
+    }
+
+    #[test]
+    fn from_code_keeps_unrecognized_code_without_prefix() {
+        assert_eq!(
+            StorageErrorKind::from_code("storage/bogus"),
+            StorageErrorKind::Unrecognized("bogus".to_owned()),
+        );
+    }
+
+    #[test]
+    fn path_problem_accepts_nested_paths() {
+        assert_eq!(path_problem("a"), None);
+        assert_eq!(path_problem("folder/sub/x.png"), None);
+    }
+
+    #[test]
+    fn path_problem_rejects_empty_segments() {
+        assert!(path_problem("").is_some());
+        assert!(path_problem("/a").is_some());
+        assert!(path_problem("a/").is_some());
+        assert!(path_problem("/").is_some());
+        assert!(path_problem("a//b").is_some());
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod wasm_tests {
+    use super::*;
+    use futures::{future, FutureExt};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn mock_ref(path: &str) -> Ref {
+        let ref_ = js_sys::Object::new();
+
+        js_sys::Reflect::set(&ref_, &"fullPath".into(), &path.into()).unwrap();
+
+        ref_.unchecked_into()
+    }
+
+    fn mock_page(items: &[&str], prefixes: &[&str], next_page_token: Option<&str>) -> ListResult {
+        let refs = |paths: &[&str]| {
+            paths
+                .iter()
+                .map(|&path| mock_ref(path))
+                .collect::<js_sys::Array>()
+        };
+
+        let page = js_sys::Object::new();
+
+        js_sys::Reflect::set(&page, &"items".into(), &refs(items)).unwrap();
+        js_sys::Reflect::set(&page, &"prefixes".into(), &refs(prefixes)).unwrap();
+
+        if let Some(token) = next_page_token {
+            js_sys::Reflect::set(&page, &"nextPageToken".into(), &token.into()).unwrap();
+        }
+
+        page.unchecked_into()
+    }
+
+    /// An `UploadTask` which only changes state when told to, and then
+    /// calls its observers synchronously, like the SDK does.
+    fn mock_task() -> UploadTask {
+        js_sys::Function::new_no_args(
+            r#"
+            return {
+                state: "running",
+                observers: [],
+                on(event, next, error, complete) {
+                    const observer = { next, error, complete };
+                    this.observers.push(observer);
+                    return () => {
+                        this.observers = this.observers.filter((o) => o !== observer);
+                    };
+                },
+                emit(state) {
+                    this.state = state;
+                    const snapshot = { state, bytesTransferred: 0, totalBytes: 1 };
+                    this.observers.forEach((o) => o.next(snapshot));
+                },
+                fail(code) {
+                    this.state = "error";
+                    this.observers.forEach((o) => o.error({ code, message: code }));
+                },
+                complete() {
+                    this.state = "success";
+                    this.observers.forEach((o) => o.complete());
+                },
+                pause() {
+                    if (this.state !== "running") return false;
+                    this.emit("paused");
+                    return true;
+                },
+                resume() {
+                    if (this.state !== "paused") return false;
+                    this.emit("running");
+                    return true;
+                },
+                cancel() {
+                    if (this.state !== "running" && this.state !== "paused") return false;
+                    this.fail("storage/canceled");
+                    return true;
+                },
+            };
+            "#,
+        )
+        .call0(&JsValue::UNDEFINED)
+        .unwrap()
+        .unchecked_into()
+    }
+
+    /// Calls one of the methods of [`mock_task`] which drive it.
+    fn drive(task: &UploadTask, method: &str, args: &[JsValue]) {
+        js_sys::Reflect::get(task, &method.into())
+            .unwrap()
+            .unchecked_into::<js_sys::Function>()
+            .apply(task, &args.iter().collect())
+            .unwrap();
+    }
+
+    fn state(item: Option<Result<UploadTaskSnapshot, StorageError>>) -> UploadState {
+        item.expect("stream to yield a snapshot")
+            .expect("snapshot not to be an error")
+            .state()
+    }
+
+    #[wasm_bindgen_test]
+    async fn pause_resume_and_cancel_reach_the_stream() {
+        let task = mock_task();
+        let mut iter = task.async_iter();
+
+        assert!(task.pause());
+        assert!(!task.pause());
+        assert_eq!(state(iter.next().await), UploadState::Paused);
+        assert!(iter.next().now_or_never().is_none());
+
+        assert!(task.resume());
+        assert_eq!(state(iter.next().await), UploadState::Running);
+
+        drive(&task, "emit", &["running".into()]);
+        assert_eq!(state(iter.next().await), UploadState::Running);
+
+        assert!(task.cancel());
+        assert!(!task.resume());
+        assert!(matches!(
+            iter.next().await,
+            Some(Err(StorageError {
+                kind: StorageErrorKind::Canceled,
+                ..
+            }))
+        ));
+        assert!(iter.next().await.is_none());
+    }
+
+    #[wasm_bindgen_test]
+    async fn snapshot_and_completion_before_the_first_poll_are_both_seen() {
+        let task = mock_task();
+        let mut iter = task.async_iter();
+
+        drive(&task, "emit", &["running".into()]);
+        drive(&task, "complete", &[]);
+
+        assert_eq!(state(iter.next().await), UploadState::Running);
+        assert!(iter.next().await.is_none());
+        assert!(iter.is_terminated());
+    }
+
+    #[wasm_bindgen_test]
+    async fn snapshot_and_error_before_the_first_poll_are_both_seen() {
+        let task = mock_task();
+        let mut iter = task.async_iter();
+
+        drive(&task, "emit", &["running".into()]);
+        drive(&task, "fail", &["storage/unauthorized".into()]);
+
+        assert_eq!(state(iter.next().await), UploadState::Running);
+        assert!(matches!(
+            iter.next().await,
+            Some(Err(StorageError {
+                kind: StorageErrorKind::Unauthorized,
+                ..
+            }))
+        ));
+        assert!(iter.next().await.is_none());
+    }
+
+    #[wasm_bindgen_test]
+    async fn list_stream_skips_prefixes_and_follows_page_tokens() {
+        let requests: Rc<RefCell<Vec<(Option<u32>, Option<String>)>>> = Rc::default();
+
+        let mut pages = VecDeque::from([
+            mock_page(&["folder/a", "folder/b"], &["folder/sub"], Some("page-2")),
+            mock_page(&["folder/c"], &[], None),
+        ]);
+
+        let list = clone!([requests], move |_, options: ListOptions| {
+            requests
+                .borrow_mut()
+                .push((options.max_results, options.page_token));
+
+            future::ready(Ok(pages.pop_front().expect("no more pages")))
+        });
+
+        let paths = list_pages(mock_ref("folder"), 2, list)
+            .map(|item| item.unwrap().full_path())
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(paths, ["folder/a", "folder/b", "folder/c"]);
+        assert_eq!(
+            *requests.borrow(),
+            [(Some(2), None), (Some(2), Some("page-2".to_owned()))],
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn list_stream_ends_after_an_error() {
+        let mut requests = 0;
+
+        let list = move |_, _| {
+            requests += 1;
+
+            assert_eq!(requests, 1, "requested a page after an error");
+
+            future::ready(Err(StorageError::new(
+                StorageErrorKind::Unauthorized,
+                "denied",
+            )))
+        };
+
+        let items = list_pages(mock_ref("folder"), 2, list)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert!(matches!(
+            items.as_slice(),
+            [Err(StorageError {
+                kind: StorageErrorKind::Unauthorized,
+                ..
+            })]
+        ));
+    }
 }
@@ -1,9 +1,18 @@
+use super::StorageError;
 use crate::app::FirebaseApp;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime},
+};
 use wasm_bindgen::prelude::*;
 
-#[derive(Debug, Clone, TypedBuilder, Serialize)]
+/// Metadata to set on an uploaded object.
+///
+/// Fields which are `None` are left out, so the SDK and server defaults
+/// apply to them.
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, Default, TypedBuilder, Serialize)]
 #[serde(rename_all = "camelCase")]
 #[builder(field_defaults(default, setter(strip_option, into)))]
 pub struct UploadMetadataOptions {
@@ -13,7 +22,8 @@ pub struct UploadMetadataOptions {
     pub content_language: Option<String>,
     pub content_type: Option<String>,
     #[builder(default, setter(skip))]
-    custom_metadata: HashMap<String, String>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub custom_metadata: HashMap<String, String>,
     pub md5_hash: Option<String>,
 }
 
@@ -24,6 +34,57 @@ impl UploadMetadataOptions {
 
         self
     }
+
+    /// Converts the metadata to the object expected by the SDK.
+    pub fn to_js(&self) -> JsValue {
+        let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+
+        self.serialize(&serializer).unwrap()
+    }
+}
+
+impl SettableMetadata {
+    /// The custom metadata as a map, which is empty if none is set.
+    pub fn custom_metadata_map(&self) -> HashMap<String, String> {
+        self.custom_metadata()
+            .and_then(|o| serde_wasm_bindgen::from_value(o.into()).ok())
+            .unwrap_or_default()
+    }
+}
+
+impl FullMetadata {
+    /// The size of the object in bytes.
+    pub fn size(&self) -> u64 {
+        self.size_js() as u64
+    }
+
+    /// When the object was created, or `None` if the SDK returned a
+    /// malformed date.
+    pub fn time_created(&self) -> Option<SystemTime> {
+        parse_time(&self.time_created_str())
+    }
+
+    /// When the object was last updated, or `None` if the SDK returned a
+    /// malformed date.
+    pub fn updated(&self) -> Option<SystemTime> {
+        parse_time(&self.updated_str())
+    }
+}
+
+fn parse_time(time: &str) -> Option<SystemTime> {
+    let millis = js_sys::Date::parse(time);
+
+    if millis.is_nan() {
+        return None;
+    }
+
+    let since_epoch = Duration::from_secs_f64(millis.abs() / 1000.0);
+
+    if millis < 0.0 {
+        SystemTime::UNIX_EPOCH.checked_sub(since_epoch)
+    } else {
+        SystemTime::UNIX_EPOCH.checked_add(since_epoch)
+    }
 }
 
 impl UploadMetadata {
@@ -41,12 +102,10 @@ pub fn upload_bytes(
     ref_: Ref,
     data: &web_sys::Blob,
     metadata: Option<UploadMetadataOptions>,
-) -> Result<UploadTask, JsValue> {
-    let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+) -> Result<UploadTask, StorageError> {
+    let metadata = metadata.map_or(JsValue::UNDEFINED, |metadata| metadata.to_js());
 
-    let metadata = metadata.serialize(&serializer).unwrap();
-
-    upload_bytes_(ref_, data, metadata)
+    upload_bytes_(ref_, data, metadata).map_err(StorageError::from_js_error)
 }
 
 #[wasm_bindgen(module = "firebase/storage")]
@@ -57,6 +116,8 @@ extern "C" {
     pub type Ref;
     pub type UploadTask;
     pub type UploadTaskSnapshot;
+    pub type UploadResult;
+    pub type ListResult;
     pub type SettableMetadata;
     #[wasm_bindgen(extends = SettableMetadata)]
     pub type UploadMetadata;
@@ -65,7 +126,10 @@ extern "C" {
     pub type TaskState;
 
     #[wasm_bindgen(js_name = getStorage)]
-    pub fn get_storage(app: Option<&FirebaseApp>, bucket_url: Option<&str>) -> Storage;
+    pub fn get_storage(app: &FirebaseApp, bucket_url: Option<&str>) -> Storage;
+
+    #[wasm_bindgen(js_name = getStorage)]
+    pub fn get_storage_default() -> Storage;
 
     #[wasm_bindgen(js_name = ref)]
     pub fn ref_(storage: Storage, path: &str) -> Ref;
@@ -77,14 +141,57 @@ extern "C" {
         metadata: JsValue,
     ) -> Result<UploadTask, JsValue>;
 
+    #[wasm_bindgen(js_name = uploadBytesResumable, catch)]
+    pub(crate) fn upload_uint8_array_resumable(
+        ref_: Ref,
+        data: &js_sys::Uint8Array,
+        metadata: JsValue,
+    ) -> Result<UploadTask, JsValue>;
+
+    #[wasm_bindgen(js_name = uploadBytes, catch)]
+    pub(crate) async fn upload_uint8_array(
+        ref_: Ref,
+        data: &js_sys::Uint8Array,
+        metadata: JsValue,
+    ) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_name = uploadString, catch)]
+    pub(crate) async fn upload_string_(
+        ref_: Ref,
+        data: &str,
+        format: &str,
+        metadata: JsValue,
+    ) -> Result<JsValue, JsValue>;
+
     #[wasm_bindgen(js_name = getDownloadURL, catch)]
-    pub async fn get_download_url(ref_: Ref) -> Result<JsValue, JsValue>;
+    pub(crate) async fn get_download_url(ref_: Ref) -> Result<JsValue, JsValue>;
 
     #[wasm_bindgen(js_name = deleteObject, catch)]
-    pub async fn delete_object(ref_: Ref) -> Result<(), JsValue>;
+    pub(crate) async fn delete_object(ref_: Ref) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(js_name = getBlob, catch)]
+    pub(crate) async fn get_blob(
+        ref_: Ref,
+        max_download_size_bytes: Option<f64>,
+    ) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_name = getBytes, catch)]
+    pub(crate) async fn get_bytes(
+        ref_: Ref,
+        max_download_size_bytes: Option<f64>,
+    ) -> Result<JsValue, JsValue>;
 
     #[wasm_bindgen(js_name = getMetadata, catch)]
-    pub async fn get_metadata(ref_: Ref) -> Result<JsValue, JsValue>;
+    pub(crate) async fn get_metadata(ref_: Ref) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_name = list, catch)]
+    pub(crate) async fn list(ref_: Ref, options: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_name = listAll, catch)]
+    pub(crate) async fn list_all(ref_: Ref) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_name = updateMetadata, catch)]
+    pub(crate) async fn update_metadata(ref_: Ref, metadata: JsValue) -> Result<JsValue, JsValue>;
 
     // =========================================================================
     //                              UploadTask
@@ -99,12 +206,21 @@ extern "C" {
         on_complete: Option<&Closure<dyn FnMut()>>,
     ) -> js_sys::Function;
 
+    /// Cancels the upload, returning whether it was still running.
+    ///
+    /// Streams from [`UploadTask::async_iter`] then end with a
+    /// `storage/canceled` error.
     #[wasm_bindgen(method)]
     pub fn cancel(this: &UploadTask) -> bool;
 
+    /// Pauses the upload, returning whether it was running.
+    ///
+    /// Streams from [`UploadTask::async_iter`] yield one snapshot in the
+    /// `paused` state, and then nothing until the upload is resumed.
     #[wasm_bindgen(method)]
     pub fn pause(this: &UploadTask) -> bool;
 
+    /// Resumes a paused upload, returning whether it was paused.
     #[wasm_bindgen(method)]
     pub fn resume(this: &UploadTask) -> bool;
 
@@ -116,23 +232,53 @@ extern "C" {
     // =========================================================================
 
     #[wasm_bindgen(method, getter, js_name = bytesTransferred)]
-    pub fn bytes_transferred(this: &UploadTaskSnapshot) -> usize;
+    pub(crate) fn bytes_transferred_js(this: &UploadTaskSnapshot) -> f64;
 
     #[wasm_bindgen(method, getter, js_name = totalBytes)]
-    pub fn total_bytes(this: &UploadTaskSnapshot) -> usize;
+    pub(crate) fn total_bytes_js(this: &UploadTaskSnapshot) -> f64;
 
     #[wasm_bindgen(method, getter, js_name = ref)]
     pub fn ref_(this: &UploadTaskSnapshot) -> Ref;
 
+    /// The metadata of the object, which is only complete once the
+    /// upload has succeeded.
     #[wasm_bindgen(method, getter)]
-    pub fn metadata(this: &UploadTaskSnapshot) -> FullMetadata;
+    pub fn metadata(this: &UploadTaskSnapshot) -> Option<FullMetadata>;
 
-    #[wasm_bindgen(method, getter)]
-    pub fn state(this: &UploadTaskSnapshot) -> String;
+    #[wasm_bindgen(method, getter, js_name = state)]
+    pub(crate) fn state_js(this: &UploadTaskSnapshot) -> String;
 
     #[wasm_bindgen(method, getter)]
     pub fn task(this: &UploadTaskSnapshot) -> UploadTask;
 
+    // =========================================================================
+    //                            UploadResult
+    // =========================================================================
+
+    #[wasm_bindgen(method, getter)]
+    pub fn metadata(this: &UploadResult) -> FullMetadata;
+
+    #[wasm_bindgen(method, getter, js_name = ref)]
+    pub fn ref_(this: &UploadResult) -> Ref;
+
+    // =========================================================================
+    //                            ListResult
+    // =========================================================================
+
+    /// The objects directly under the listed reference.
+    #[wasm_bindgen(method, getter)]
+    pub fn items(this: &ListResult) -> Vec<Ref>;
+
+    /// The "folders" directly under the listed reference, which contain
+    /// more objects.
+    #[wasm_bindgen(method, getter)]
+    pub fn prefixes(this: &ListResult) -> Vec<Ref>;
+
+    /// The token to pass as [`ListOptions::page_token`](super::ListOptions::page_token)
+    /// to get the next page, or `None` if this was the last page.
+    #[wasm_bindgen(method, getter, js_name = nextPageToken)]
+    pub fn next_page_token(this: &ListResult) -> Option<String>;
+
     // =========================================================================
     //                            SettableMetadata
     // =========================================================================
@@ -181,17 +327,18 @@ extern "C" {
     #[wasm_bindgen(method, getter)]
     pub fn name(this: &FullMetadata) -> String;
 
-    #[wasm_bindgen(method, getter)]
-    pub fn size(this: &FullMetadata) -> u64;
+    // JS numbers can't be converted to `u64` directly
+    #[wasm_bindgen(method, getter, js_name = size)]
+    fn size_js(this: &FullMetadata) -> f64;
 
     #[wasm_bindgen(method, getter, js_name = timeCreated)]
-    pub fn time_created(this: &FullMetadata) -> String;
+    pub fn time_created_str(this: &FullMetadata) -> String;
 
-    #[wasm_bindgen(method, getter)]
-    pub fn updated(this: &FullMetadata) -> String;
+    #[wasm_bindgen(method, getter, js_name = updated)]
+    pub fn updated_str(this: &FullMetadata) -> String;
 
     #[wasm_bindgen(method, getter, js_name = downloadTokens)]
-    pub fn download_tokens(this: &FullMetadata) -> Vec<js_sys::JsString>;
+    pub fn download_tokens(this: &FullMetadata) -> Option<Vec<js_sys::JsString>>;
 
     #[wasm_bindgen(method, getter, js_name = ref)]
     pub fn ref_(this: &FullMetadata) -> Option<Ref>;
@@ -0,0 +1,110 @@
+//! Combinators for the listener streams in this crate.
+
+use futures::{Stream, StreamExt};
+use gloo::timers::callback::Timeout;
+use std::{
+    cell::{Cell, RefCell},
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+pub trait CoalesceExt: Stream + Sized {
+    /// Yields at most one item per `window`, dropping all but the latest
+    /// item received during the window.
+    ///
+    /// The first item is yielded right away, and the latest item is always
+    /// yielded, so the stream never ends on a stale value.
+    fn coalesce(self, window: Duration) -> Coalesce<Self> {
+        Coalesce {
+            stream: self,
+            window,
+            latest: None,
+            stream_done: false,
+            timeout: None,
+            window_elapsed: Rc::default(),
+            waker: Rc::default(),
+        }
+    }
+}
+
+impl<S: Stream> CoalesceExt for S {}
+
+/// See [`CoalesceExt::coalesce`].
+///
+/// Dropping this cancels the pending timer, if any.
+pub struct Coalesce<S: Stream> {
+    stream: S,
+    window: Duration,
+    latest: Option<S::Item>,
+    stream_done: bool,
+    timeout: Option<Timeout>,
+    window_elapsed: Rc<Cell<bool>>,
+    waker: Rc<RefCell<Option<Waker>>>,
+}
+
+impl<S: Stream> Coalesce<S> {
+    fn start_window(&mut self) {
+        let window_elapsed = self.window_elapsed.clone();
+        let waker = self.waker.clone();
+
+        window_elapsed.set(false);
+
+        let millis = self.window.as_millis().try_into().unwrap_or(u32::MAX);
+
+        self.timeout = Some(Timeout::new(millis, move || {
+            window_elapsed.set(true);
+
+            if let Some(w) = waker.borrow().as_ref() {
+                w.wake_by_ref();
+            }
+        }));
+    }
+}
+
+// Items are never pinned, so only `S` needs to be `Unpin`
+impl<S: Stream + Unpin> Unpin for Coalesce<S> {}
+
+impl<S: Stream + Unpin> Stream for Coalesce<S> {
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        // Update waker
+        *this.waker.borrow_mut() = Some(cx.waker().to_owned());
+
+        while !this.stream_done {
+            match this.stream.poll_next_unpin(cx) {
+                Poll::Ready(Some(item)) => this.latest = Some(item),
+                Poll::Ready(None) => this.stream_done = true,
+                Poll::Pending => break,
+            }
+        }
+
+        if this.timeout.is_some() && this.window_elapsed.get() {
+            this.timeout = None;
+        }
+
+        // Flush the latest item right away once the stream has ended, since
+        // nothing newer can replace it
+        if this.timeout.is_none() || this.stream_done {
+            if let Some(item) = this.latest.take() {
+                if !this.stream_done {
+                    this.start_window();
+                }
+
+                return Poll::Ready(Some(item));
+            }
+        }
+
+        if this.stream_done {
+            this.timeout = None;
+
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
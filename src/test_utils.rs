@@ -0,0 +1,72 @@
+use crate::app::{initialize_app, FirebaseApp, FirebaseConfig};
+
+/// The project of the app returned by [`app`], which bundles and paths
+/// built by tests must match.
+pub(crate) const PROJECT_ID: &str = "demo-test";
+
+thread_local! {
+    static APP: FirebaseApp = initialize_app(
+        &FirebaseConfig::builder()
+            .api_key("demo-key")
+            .project_id(PROJECT_ID)
+            .app_id("demo-app")
+            .build(),
+        None,
+    )
+    .expect("test app to initialize");
+}
+
+/// The app shared by all tests. It never talks to a server, so only
+/// local SDK behaviour can be tested with it.
+pub(crate) fn app() -> FirebaseApp {
+    APP.with(Clone::clone)
+}
+
+#[cfg(feature = "firestore")]
+pub(crate) fn firestore() -> crate::firestore::Firestore {
+    crate::firestore::get_firestore(&app())
+}
+
+/// The resource name which document names in bundles are relative to.
+#[cfg(feature = "firestore")]
+pub(crate) fn documents_root() -> String {
+    format!("projects/{PROJECT_ID}/databases/(default)/documents")
+}
+
+/// The resource name of the document at `path`, as used in bundles.
+#[cfg(feature = "firestore")]
+pub(crate) fn document_name(path: &str) -> String {
+    format!("{}/{path}", documents_root())
+}
+
+/// The metadata and document elements of a bundled document, with
+/// `fields` encoded like in the REST API.
+#[cfg(feature = "firestore")]
+pub(crate) fn bundled_document(path: &str, fields: &str) -> [String; 2] {
+    let name = document_name(path);
+    let time = r#"{"seconds":1,"nanos":0}"#;
+
+    [
+        format!(r#"{{"documentMetadata":{{"name":"{name}","readTime":{time},"exists":true}}}}"#),
+        format!(
+            r#"{{"document":{{"name":"{name}","fields":{fields},"createTime":{time},"updateTime":{time}}}}}"#
+        ),
+    ]
+}
+
+/// Encodes a bundle the way the server does, with each JSON element
+/// prefixed by its length in bytes.
+#[cfg(feature = "firestore")]
+pub(crate) fn bundle(total_documents: usize, elements: &[String]) -> Vec<u8> {
+    let body = elements
+        .iter()
+        .map(|element| format!("{}{element}", element.len()))
+        .collect::<String>();
+
+    let metadata = format!(
+        r#"{{"metadata":{{"id":"test-bundle","createTime":{{"seconds":1,"nanos":0}},"version":1,"totalDocuments":{total_documents},"totalBytes":{}}}}}"#,
+        body.len(),
+    );
+
+    format!("{}{metadata}{body}", metadata.len()).into_bytes()
+}
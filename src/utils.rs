@@ -5,3 +5,28 @@ macro_rules! console_log {
         gloo::console::log!($($tt)*);
     };
 }
+
+// Stand-ins for the `tracing` macros when the feature is disabled, so that
+// call sites don't need to be gated
+#[cfg(not(feature = "tracing"))]
+#[allow(unused_macros)]
+macro_rules! trace {
+    // Still type-check the message, so captured variables count as used
+    ($fmt:literal $(, $arg:expr)* $(,)?) => {
+        if false {
+            let _ = format_args!($fmt $(, $arg)*);
+        }
+    };
+    ($($tt:tt)*) => {};
+}
+
+#[cfg(not(feature = "tracing"))]
+#[allow(unused_macros)]
+macro_rules! error {
+    ($fmt:literal $(, $arg:expr)* $(,)?) => {
+        if false {
+            let _ = format_args!($fmt $(, $arg)*);
+        }
+    };
+    ($($tt:tt)*) => {};
+}